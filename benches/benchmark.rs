@@ -1,10 +1,13 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 
 use rand::{rngs::StdRng, SeedableRng};
 use rs_a::{
     crypto::RsaPrimitive,
-    keygen::{KeyPairBuilder, RsaCsprng},
-    util::{carmichael_totient, generate_candidate_prime, miller_rabin_is_prime},
+    keygen::{KeyPairBuilder, RsaCsprng, RsaPrivateKey, RSA_PRIME_NUMBER_BIT_LENGTH},
+    util::{
+        carmichael_totient, generate_candidate_prime, generate_prime_with_test,
+        miller_rabin_is_prime, MillerRabin, SieveSize,
+    },
 };
 
 use num::{BigUint, Integer};
@@ -19,11 +22,50 @@ pub fn candidate_prime_benchmark(c: &mut Criterion) {
 
     c.bench_function("candidate primes", |b| {
         b.iter(|| {
-            black_box(generate_candidate_prime(&mut rng, ITERATIONS));
+            black_box(
+                generate_candidate_prime(&mut rng, ITERATIONS, RSA_PRIME_NUMBER_BIT_LENGTH)
+                    .unwrap(),
+            );
         })
     });
 }
 
+// Generates a candidate prime with each sieve size.
+pub fn sieve_size_benchmark(c: &mut Criterion) {
+    let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::from_entropy());
+    let primality_test = MillerRabin::new(MILLER_RABIN_ITERATIONS);
+    let mut group = c.benchmark_group("candidate primes (sieve size)");
+    group.sample_size(50);
+
+    for sieve_size in [
+        SieveSize::Primes256,
+        SieveSize::Primes512,
+        SieveSize::Primes1024,
+        SieveSize::Primes2048,
+        SieveSize::Primes4096,
+    ] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(sieve_size.size()),
+            &sieve_size,
+            |b, sieve_size| {
+                b.iter(|| {
+                    black_box(
+                        generate_prime_with_test(
+                            &mut rng,
+                            &primality_test,
+                            RSA_PRIME_NUMBER_BIT_LENGTH,
+                            *sieve_size,
+                        )
+                        .unwrap(),
+                    );
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
 // computes `PRIMES` primes, then performs the Miller-Rabin
 // Primality test on each prime with `ITERATIONS` iterations
 // on each prime.
@@ -34,7 +76,14 @@ pub fn miller_rabin_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("miller_rabin");
 
     for _ in 0..PRIMES {
-        candidates.push(generate_candidate_prime(&mut rng, MILLER_RABIN_ITERATIONS));
+        candidates.push(
+            generate_candidate_prime(
+                &mut rng,
+                MILLER_RABIN_ITERATIONS,
+                RSA_PRIME_NUMBER_BIT_LENGTH,
+            )
+            .unwrap(),
+        );
     }
 
     group.bench_function("miller-rabin", |b| {
@@ -75,6 +124,8 @@ pub fn encryption_decryption_bench(c: &mut Criterion) {
         })
     });
 
+    // reuses what the key caches on its first operation: the blinding factors and
+    // the Montgomery parameters of n, p and q. The next one pays for both on every operation.
     group.bench_function("decryption primitive (CRT)", |b| {
         b.iter(|| {
             let m = rng.gen_biguint(BIT_SIZE);
@@ -83,6 +134,28 @@ pub fn encryption_decryption_bench(c: &mut Criterion) {
         })
     });
 
+    group.bench_function("decryption primitive (CRT, new key each time)", |b| {
+        let sk = &kp.private_key;
+        b.iter_batched(
+            || {
+                let key = RsaPrivateKey::new(
+                    sk.version,
+                    sk.modulus.clone(),
+                    sk.public_exponent.clone(),
+                    sk.private_exponent.clone(),
+                    sk.prime1.clone(),
+                    sk.prime2.clone(),
+                    sk.exponent1.clone(),
+                    sk.exponent2.clone(),
+                    sk.coefficient.clone(),
+                );
+                (key, rng.gen_biguint(BIT_SIZE))
+            },
+            |(key, m)| black_box(key.crypt(&m).unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+
     group.bench_function("decryption primitive (exponent)", |b| {
         b.iter(|| {
             let m = rng.gen_biguint(BIT_SIZE);
@@ -95,7 +168,7 @@ pub fn encryption_decryption_bench(c: &mut Criterion) {
             let m = rng.gen_biguint(BIT_SIZE);
 
             let e = &kp.public_key.crypt(&m).unwrap();
-            let d = &kp.private_key.crypt(&e).unwrap();
+            let d = &kp.private_key.crypt(e).unwrap();
 
             assert_eq!(*d, m);
         })
@@ -108,8 +181,18 @@ pub fn exponent_benchmark(c: &mut Criterion) {
 
     let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::from_entropy());
 
-    let p = generate_candidate_prime(&mut rng, MILLER_RABIN_ITERATIONS);
-    let q = generate_candidate_prime(&mut rng, MILLER_RABIN_ITERATIONS);
+    let p = generate_candidate_prime(
+        &mut rng,
+        MILLER_RABIN_ITERATIONS,
+        RSA_PRIME_NUMBER_BIT_LENGTH,
+    )
+    .unwrap();
+    let q = generate_candidate_prime(
+        &mut rng,
+        MILLER_RABIN_ITERATIONS,
+        RSA_PRIME_NUMBER_BIT_LENGTH,
+    )
+    .unwrap();
 
     let lambda = carmichael_totient(&p, &q);
 
@@ -151,7 +234,7 @@ pub fn exponent_benchmark(c: &mut Criterion) {
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(250);
-    targets = candidate_prime_benchmark, miller_rabin_benchmark, keypair_builder_benchmark, exponent_benchmark, encryption_decryption_bench
+    targets = candidate_prime_benchmark, sieve_size_benchmark, miller_rabin_benchmark, keypair_builder_benchmark, exponent_benchmark, encryption_decryption_bench
     // targets = encryption_decryption_bench
 }
 criterion_main!(benches);
@@ -0,0 +1,56 @@
+//! Generates the table of small primes used to sieve candidate primes during key generation.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// The number of primes in the table; the largest supported sieve size.
+const PRECOMPUTED_PRIMES_LEN: usize = 4096;
+
+/// Returns the first `n` primes, using the sieve of Eratosthenes.
+fn primes(n: usize) -> Vec<u64> {
+    // the n-th prime is less than n * (ln n + ln ln n) for n >= 6
+    let ln = (n as f64).ln();
+    let limit = ((n as f64) * (ln + ln.ln())).ceil() as usize + 16;
+
+    let mut composite = vec![false; limit];
+    let mut ret = Vec::with_capacity(n);
+
+    for i in 2..limit {
+        if composite[i] {
+            continue;
+        }
+
+        ret.push(i as u64);
+        if ret.len() == n {
+            break;
+        }
+
+        for j in (i * i..limit).step_by(i) {
+            composite[j] = true;
+        }
+    }
+
+    ret
+}
+
+fn main() {
+    let primes = primes(PRECOMPUTED_PRIMES_LEN);
+    assert_eq!(primes.len(), PRECOMPUTED_PRIMES_LEN);
+
+    let mut table = format!(
+        "pub const PRECOMPUTED_PRIMES_LEN: usize = {};\n\
+         pub static PRECOMPUTED_PRIMES: [u64; PRECOMPUTED_PRIMES_LEN] = [\n",
+        PRECOMPUTED_PRIMES_LEN
+    );
+    for chunk in primes.chunks(16) {
+        let line: Vec<String> = chunk.iter().map(u64::to_string).collect();
+        table.push_str(&format!("    {},\n", line.join(", ")));
+    }
+    table.push_str("];\n");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("primes.rs");
+    fs::write(out, table).unwrap();
+
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -2,14 +2,38 @@
 //! This module handles cryptographic primitives, as well as
 //! the associated encryption/decryption operations.
 
-// might want to switch to crypto-bigint for faster modular operations
-use num::BigUint;
+use hmac::{Hmac, Mac};
+use num::{BigUint, One};
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use std::sync::{Mutex, PoisonError};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeLess};
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
     errors::{RsaError, RsaErrorKind},
-    keygen::{RsaPrivateKey, RsaPublicKey},
+    keygen::{RsaCsprng, RsaPrivateKey, RsaPublicKey},
+    mask::{HashType, MaskGenerationFunction, Mgf1},
+    util::{i2osp, os2ip, zeroize_biguint},
 };
 
+#[cfg(feature = "constant-time")]
+use crate::montgomery;
+use crate::montgomery::Modulus;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The minimum length of the padding string PS in EME-PKCS1-v1_5.
+const PKCS1V15_MIN_PADDING_LEN: usize = 8;
+
+/// The number of candidate lengths drawn when picking the length of a synthetic message.
+const IMPLICIT_REJECTION_CANDIDATES: usize = 128;
+
+/// The number of private key operations one set of blinding factors is used for,
+/// squaring them between operations, before fresh factors are drawn. As in OpenSSL.
+const BLINDING_REFRESH_INTERVAL: u32 = 32;
+
 /// This trait is used to implement the RSA Encryption/Decryption primitives.
 /// Namely, RSAEP and RSADP. The definitions for these primitives can be found
 /// [in Section 5 of RFC8017](https://www.rfc-editor.org/rfc/rfc8017#section-5).
@@ -19,17 +43,117 @@ pub trait RsaPrimitive {
     /// or else the operation fails, and returns an `RsaCryptographyError`.
     fn crypt(&self, message: &BigUint) -> Result<BigUint, RsaError>;
 
+    /// Performs the same operation as `crypt` on a big-endian octet string, converting
+    /// with OS2IP and I2OSP. The output is always exactly as long as the modulus.
     fn crypt_with_bytes(&self, message: &[u8]) -> Result<Vec<u8>, RsaError>;
 }
 
+/// What a private key does when the result of a CRT computation doesn't verify,
+/// i.e. when `m^e != c mod n`. Releasing a faulty CRT result would leak a factor of `n`
+/// (the Bellcore attack, by Boneh, DeMillo and Lipton),
+/// so it's never returned.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FaultRecovery {
+    /// Fail with a `CryptographyError`.
+    #[default]
+    Fail,
+    /// Recompute the result without CRT, as `c^d mod n`, which is several times slower.
+    /// Fail with a `CryptographyError` if that result doesn't verify either.
+    NonCrt,
+}
+
+/// The hash function and the mask generation function used by RSAES-OAEP.
+///
+/// By default, SHA-256 is used both for hashing the label and within MGF1.
+/// Another mask generation function can be used with `OaepParams::with_mgf`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OaepParams<M: MaskGenerationFunction = Mgf1> {
+    /// The hash function applied to the label.
+    pub hash: HashType,
+    /// The mask generation function.
+    pub mgf: M,
+}
+
+impl Default for OaepParams {
+    fn default() -> Self {
+        Self::new(HashType::Sha256, HashType::Sha256)
+    }
+}
+
+impl OaepParams {
+    /// Uses MGF1 with `mgf_hash` as the mask generation function.
+    pub fn new(hash: HashType, mgf_hash: HashType) -> Self {
+        Self {
+            hash,
+            mgf: Mgf1::new(mgf_hash),
+        }
+    }
+}
+
+impl<M: MaskGenerationFunction> OaepParams<M> {
+    pub fn with_mgf(hash: HashType, mgf: M) -> Self {
+        Self { hash, mgf }
+    }
+}
+
+/// [RSAES-OAEP-ENCRYPT](https://www.rfc-editor.org/rfc/rfc8017#section-7.1.1)
 pub trait RsaOaepEncrypt {
-    fn encrypt(
+    /// Encrypts `message` under the optional `label`, drawing the OAEP seed from `rng`.
+    ///
+    /// The resulting ciphertext is always exactly as long as the modulus.
+    fn encrypt<R: RsaCsprng + ?Sized, M: MaskGenerationFunction>(
         &self,
+        rng: &mut R,
+        params: &OaepParams<M>,
         message: impl AsRef<[u8]>,
-        label: Option<impl AsRef<[u8]>>,
+        label: Option<&[u8]>,
     ) -> Result<Vec<u8>, RsaError>;
 }
 
+/// [RSAES-OAEP-DECRYPT](https://www.rfc-editor.org/rfc/rfc8017#section-7.1.2)
+pub trait RsaOaepDecrypt {
+    /// Decrypts `ciphertext`, which must have been encrypted under the same `label`.
+    ///
+    /// Every failure returns the same `decryption error`, so that callers can't
+    /// learn which step of the decoding went wrong.
+    fn decrypt<M: MaskGenerationFunction>(
+        &self,
+        params: &OaepParams<M>,
+        ciphertext: impl AsRef<[u8]>,
+        label: Option<&[u8]>,
+    ) -> Result<Vec<u8>, RsaError>;
+}
+
+/// [RSAES-PKCS1-v1_5-ENCRYPT](https://www.rfc-editor.org/rfc/rfc8017#section-7.2.1)
+///
+/// Only recommended for compatibility with legacy applications; prefer `RsaOaepEncrypt`.
+pub trait RsaPkcs1v15Encrypt {
+    /// Encrypts `message`, drawing the nonzero padding string from `rng`.
+    ///
+    /// The resulting ciphertext is always exactly as long as the modulus.
+    fn encrypt_pkcs1v15<R: RsaCsprng + ?Sized>(
+        &self,
+        rng: &mut R,
+        message: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, RsaError>;
+}
+
+/// [RSAES-PKCS1-v1_5-DECRYPT](https://www.rfc-editor.org/rfc/rfc8017#section-7.2.2),
+/// using implicit rejection as described in
+/// [draft-irtf-cfrg-rsa-guidance](https://datatracker.ietf.org/doc/draft-irtf-cfrg-rsa-guidance/).
+pub trait RsaPkcs1v15Decrypt {
+    /// Decrypts `ciphertext`.
+    ///
+    /// When the padding is malformed, no error is returned. Instead, a synthetic message
+    /// derived deterministically from the private key and the ciphertext is returned, so
+    /// that an attacker can't use the decryption as a padding oracle (Bleichenbacher's attack).
+    /// This matches the behaviour of OpenSSL 3.2 and later.
+    ///
+    /// An error is only returned when the ciphertext isn't exactly as long as the modulus,
+    /// or when its integer representative is out of range.
+    fn decrypt_pkcs1v15(&self, ciphertext: impl AsRef<[u8]>) -> Result<Vec<u8>, RsaError>;
+}
+
 impl RsaPrimitive for RsaPublicKey {
     fn crypt(&self, message: &BigUint) -> Result<BigUint, RsaError> {
         if message >= &self.modulus {
@@ -39,16 +163,24 @@ impl RsaPrimitive for RsaPublicKey {
             ));
         }
 
-        Ok(message.modpow(&self.public_exponent, &self.modulus))
+        Ok(self
+            .precomputed
+            .pow_public(&[&self.modulus], 0, message, &self.public_exponent))
     }
 
     fn crypt_with_bytes(&self, message: &[u8]) -> Result<Vec<u8>, RsaError> {
-        let res = self.crypt(&BigUint::from_bytes_le(&message))?;
-        Ok(res.to_bytes_le())
+        let res = self.crypt(&os2ip(message))?;
+        i2osp(&res, self.modulus_len())
     }
 }
 
 impl RsaPrimitive for RsaPrivateKey {
+    /// Unless blinding is turned off with `RsaPrivateKey::with_blinding`, the ciphertext
+    /// is blinded before exponentiation, so the timing of the operation doesn't depend on it:
+    /// `m = (c * r^e)^d * r^(-1) mod n` for a random `r`.
+    ///
+    /// The CRT result is checked against the public key before it's returned.
+    /// See `FaultRecovery` for what happens if the check fails.
     fn crypt(&self, ciphertext: &BigUint) -> Result<BigUint, RsaError> {
         if ciphertext >= &self.modulus {
             return Err(RsaError::new(
@@ -57,23 +189,1250 @@ impl RsaPrimitive for RsaPrivateKey {
             ));
         }
 
+        if !self.blinding.enabled {
+            return self.checked_crypt(ciphertext);
+        }
+
+        let factors = self.blinding.take(&self.modulus, &self.public_exponent);
+
+        let mut blinded = ciphertext * &factors.blind % &self.modulus;
+        let m = self.checked_crypt(&blinded);
+        zeroize_biguint(&mut blinded);
+
+        // on a fault, the factors are dropped rather than cached
+        let mut m = m?;
+        m *= &factors.unblind;
+        m %= &self.modulus;
+
+        self.blinding.put(factors, &self.modulus);
+
+        Ok(m)
+    }
+
+    fn crypt_with_bytes(&self, message: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let res = self.crypt(&os2ip(message))?;
+        i2osp(&res, self.modulus_len())
+    }
+}
+
+impl RsaPrivateKey {
+    /// RSADP with the CRT values of the key, checking that `m^e = c mod n` before returning `m`.
+    fn checked_crypt(&self, ciphertext: &BigUint) -> Result<BigUint, RsaError> {
+        let moduli = self.moduli();
+        let verifies = |m: &BigUint| {
+            self.precomputed
+                .pow_public(&moduli, 0, m, &self.public_exponent)
+                == *ciphertext
+        };
+
+        let mut m = self.crt_crypt(&moduli, ciphertext);
+        if verifies(&m) {
+            return Ok(m);
+        }
+        zeroize_biguint(&mut m);
+
+        if self.fault_recovery == FaultRecovery::NonCrt {
+            let mut m = self
+                .precomputed
+                .pow_secret(&moduli, 0, ciphertext, &self.private_exponent);
+            if verifies(&m) {
+                return Ok(m);
+            }
+            zeroize_biguint(&mut m);
+        }
+
+        Err(RsaError::new(
+            RsaErrorKind::CryptographyError,
+            String::from("fault detected: the result doesn't match the ciphertext"),
+        ))
+    }
+
+    /// The moduli that operations with the key reduce by, in the order their precomputed
+    /// values are kept in: `n`, `p`, `q`, then `r_3, ..., r_u`.
+    fn moduli(&self) -> Vec<&BigUint> {
+        [&self.modulus, &self.prime1, &self.prime2]
+            .into_iter()
+            .chain(self.other_prime_infos.iter().map(|info| &info.prime))
+            .collect()
+    }
+
+    /// RSADP with the CRT values of the key, without blinding.
+    ///
+    /// With the `constant-time` feature, the recombination runs on fixed-width arithmetic
+    /// too. See `montgomery::crt_pow`.
+    fn crt_crypt(&self, moduli: &[&BigUint], ciphertext: &BigUint) -> BigUint {
+        #[cfg(feature = "constant-time")]
+        {
+            let exponents: Vec<&BigUint> = [&self.exponent1, &self.exponent2]
+                .into_iter()
+                .chain(self.other_prime_infos.iter().map(|info| &info.exponent))
+                .collect();
+            let coefficients: Vec<&BigUint> = std::iter::once(&self.coefficient)
+                .chain(self.other_prime_infos.iter().map(|info| &info.coefficient))
+                .collect();
+
+            if let Some(m) = self
+                .precomputed
+                .crt_pow(moduli, ciphertext, &exponents, &coefficients)
+            {
+                return m;
+            }
+        }
+
         // m_1 = c^dP mod p
         // m_2 = c^dQ mod q
-        let mut m_1 = ciphertext.modpow(&self.exponent1, &self.prime1);
-        let m_2 = ciphertext.modpow(&self.exponent2, &self.prime2);
+        let mut m_1 = self
+            .precomputed
+            .pow_secret(moduli, 1, ciphertext, &self.exponent1);
+        let mut m_2 = self
+            .precomputed
+            .pow_secret(moduli, 2, ciphertext, &self.exponent2);
 
         // m_1 - m_2 mod p = m_1 mod p - m_2 mod p
 
-        let m_2r = &m_2 % &self.prime1;
-        m_1 = (m_1 + &self.prime1 - &m_2r) % &self.prime1;
+        let mut m_2r = &m_2 % &self.prime1;
+        m_1 += &self.prime1;
+        m_1 -= &m_2r;
+        m_1 %= &self.prime1;
+
+        let mut h = &m_1 * &self.coefficient;
+        h %= &self.prime1;
+        h *= &self.prime2;
+
+        let mut m = &m_2 + &h;
+
+        // the CRT intermediates are as secret as the key itself
+        for x in [&mut m_1, &mut m_2, &mut m_2r, &mut h] {
+            zeroize_biguint(x);
+        }
+
+        // for multi-prime keys, fold in each m_i = c^d_i mod r_i:
+        // R = r_1 * r_2 * ... * r_(i-1)
+        // h = (m_i - m) * t_i mod r_i
+        // m = m + R * h
+        let mut r = &self.prime1 * &self.prime2;
+        for (i, info) in self.other_prime_infos.iter().enumerate() {
+            let mut m_i = self
+                .precomputed
+                .pow_secret(moduli, 3 + i, ciphertext, &info.exponent);
 
-        let h = (m_1 * &self.coefficient) % &self.prime1;
+            let mut m_r = &m % &info.prime;
+            m_i += &info.prime;
+            m_i -= &m_r;
 
-        Ok(m_2 + &self.prime2 * h)
+            let mut h = &m_i * &info.coefficient;
+            h %= &info.prime;
+            h *= &r;
+
+            m += &h;
+            r *= &info.prime;
+
+            for x in [&mut m_i, &mut m_r, &mut h] {
+                zeroize_biguint(x);
+            }
+        }
+        zeroize_biguint(&mut r);
+
+        m
+    }
+}
+
+/// `base^exponent mod modulus` for a secret `exponent`.
+///
+/// With the `constant-time` feature, this runs on fixed-width Montgomery arithmetic
+/// rather than `num-bigint`, so its timing doesn't depend on the exponent or the base.
+/// Even moduli, which no valid key has, are left to `num-bigint`.
+fn pow_secret(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    #[cfg(feature = "constant-time")]
+    if let Some(modulus) = Modulus::new(modulus) {
+        return modulus.pow(base, exponent);
     }
 
-    fn crypt_with_bytes(&self, message: &[u8]) -> Result<Vec<u8>, RsaError> {
-        let res = self.crypt(&BigUint::from_bytes_le(&message))?;
-        Ok(res.to_bytes_le())
+    base.modpow(exponent, modulus)
+}
+
+/// `base^exponent mod modulus` for a public `exponent`, like `pow_secret`
+/// except that the timing may depend on the bit length of the exponent.
+fn pow_public(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    #[cfg(feature = "constant-time")]
+    if let Some(modulus) = Modulus::new(modulus) {
+        return modulus.pow_vartime(base, exponent);
+    }
+
+    base.modpow(exponent, modulus)
+}
+
+/// Values precomputed from the moduli of a key (`n`, and for a private key its primes),
+/// which every operation with the key would otherwise compute again.
+///
+/// These are the Montgomery parameters of each modulus (`R mod m`, `R^2 mod m` and
+/// `-m^(-1) mod 2^64`), computed the first time the key is used. Parameters that no longer
+/// match the key, because one of its moduli was changed since, are ignored.
+///
+/// The parameters of the primes are as secret as the primes. They're wiped when they're
+/// dropped, except with the `constant-time` feature, since `crypto-bigint` doesn't.
+#[derive(Clone, Default)]
+pub(crate) struct Precomputed {
+    moduli: OnceLock<Vec<Option<Modulus>>>,
+}
+
+impl Precomputed {
+    /// `base^exponent mod moduli[index]` for a secret `exponent`. `moduli` are all the
+    /// moduli of the key, always passed in the same order. See `pow_secret`.
+    fn pow_secret(
+        &self,
+        moduli: &[&BigUint],
+        index: usize,
+        base: &BigUint,
+        exponent: &BigUint,
+    ) -> BigUint {
+        if let Some(modulus) = self.get(moduli, index) {
+            return modulus.pow(base, exponent);
+        }
+
+        pow_secret(base, exponent, moduli[index])
+    }
+
+    /// `base^exponent mod moduli[index]` for a public `exponent`. See `pow_public`.
+    fn pow_public(
+        &self,
+        moduli: &[&BigUint],
+        index: usize,
+        base: &BigUint,
+        exponent: &BigUint,
+    ) -> BigUint {
+        if let Some(modulus) = self.get(moduli, index) {
+            return modulus.pow_vartime(base, exponent);
+        }
+
+        pow_public(base, exponent, moduli[index])
+    }
+
+    /// `base^d mod moduli[0]` from the CRT values of a key, or `None` if a modulus has no
+    /// parameters. See `montgomery::crt_pow`.
+    #[cfg(feature = "constant-time")]
+    fn crt_pow(
+        &self,
+        moduli: &[&BigUint],
+        base: &BigUint,
+        exponents: &[&BigUint],
+        coefficients: &[&BigUint],
+    ) -> Option<BigUint> {
+        let params = (0..moduli.len())
+            .map(|index| self.get(moduli, index))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(montgomery::crt_pow(&params, base, exponents, coefficients))
+    }
+
+    /// The parameters of `moduli[index]`, computing those of every modulus if this is the
+    /// first time they're needed.
+    fn get(&self, moduli: &[&BigUint], index: usize) -> Option<&Modulus> {
+        let modulus = self
+            .moduli
+            .get_or_init(|| moduli.iter().map(|m| Modulus::new(m)).collect())
+            .get(index)?
+            .as_ref()?;
+
+        modulus.is_for(moduli[index]).then_some(modulus)
+    }
+
+    /// Drops the precomputed values.
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// The precomputed values don't affect the result of any operation, so they're ignored.
+impl PartialEq for Precomputed {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Precomputed {}
+
+/// A pair of base blinding factors `blind = r^e mod n` and `unblind = r^(-1) mod n`.
+struct BlindingFactors {
+    blind: BigUint,
+    unblind: BigUint,
+    /// The number of operations these factors have been used for.
+    uses: u32,
+}
+
+impl BlindingFactors {
+    /// Draws a random `r` in `[1, n)` that is invertible mod `n`.
+    fn new<R: RsaCsprng + ?Sized>(rng: &mut R, n: &BigUint, e: &BigUint) -> Self {
+        loop {
+            let mut r = rng.gen_biguint_range(&BigUint::one(), n);
+
+            if let Some(unblind) = r.modinv(n) {
+                let blind = pow_public(&r, e, n);
+                zeroize_biguint(&mut r);
+
+                return Self {
+                    blind,
+                    unblind,
+                    uses: 0,
+                };
+            }
+        }
+    }
+}
+
+impl Drop for BlindingFactors {
+    fn drop(&mut self) {
+        zeroize_biguint(&mut self.blind);
+        zeroize_biguint(&mut self.unblind);
+    }
+}
+
+/// The blinding state of a private key: whether private key operations are blinded,
+/// and the cached blinding factors.
+///
+/// Each operation takes the cached factors, and puts them back squared
+/// (`r` becomes `r^2`), which is much cheaper than drawing a fresh `r`.
+/// Every `BLINDING_REFRESH_INTERVAL` operations, fresh factors are drawn from the key's
+/// CSPRNG: the one given to `RsaPrivateKey::with_blinding_rng`, or else a `StdRng` seeded
+/// from the operating system. Operations running concurrently on the same key
+/// draw their own factors rather than waiting for the cached ones.
+pub(crate) struct Blinding {
+    pub(crate) enabled: bool,
+    factors: Mutex<Option<BlindingFactors>>,
+    rng: Mutex<Option<Box<dyn RsaCsprng + Send>>>,
+}
+
+impl Blinding {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            factors: Mutex::new(None),
+            rng: Mutex::new(None),
+        }
+    }
+
+    /// Draws fresh factors from `rng` from now on.
+    pub(crate) fn set_rng(&mut self, rng: Box<dyn RsaCsprng + Send>) {
+        self.clear();
+        *self.rng.get_mut().unwrap_or_else(PoisonError::into_inner) = Some(rng);
+    }
+
+    fn take(&self, n: &BigUint, e: &BigUint) -> BlindingFactors {
+        let cached = self
+            .factors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        match cached {
+            Some(factors) if factors.uses < BLINDING_REFRESH_INTERVAL => factors,
+            _ => {
+                let mut rng = self.rng.lock().unwrap_or_else(PoisonError::into_inner);
+                let rng = rng.get_or_insert_with(|| Box::new(StdRng::from_entropy()));
+
+                BlindingFactors::new(rng.as_mut(), n, e)
+            }
+        }
+    }
+
+    fn put(&self, mut factors: BlindingFactors, n: &BigUint) {
+        factors.blind = factors.blind.modpow(&BigUint::from(2u32), n);
+        factors.unblind = factors.unblind.modpow(&BigUint::from(2u32), n);
+        factors.uses += 1;
+
+        *self.factors.lock().unwrap_or_else(PoisonError::into_inner) = Some(factors);
+    }
+
+    /// Drops the cached factors, which wipes them.
+    pub(crate) fn clear(&self) {
+        self.factors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+}
+
+impl Default for Blinding {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+/// A clone draws its own blinding factors, from a `StdRng` seeded by the key's CSPRNG
+/// if it has been given one.
+impl Clone for Blinding {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new(self.enabled);
+
+        if let Some(rng) = self
+            .rng
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+        {
+            let mut seed = <StdRng as SeedableRng>::Seed::default();
+            rng.fill_bytes(&mut seed);
+            clone.set_rng(Box::new(StdRng::from_seed(seed)));
+            seed.zeroize();
+        }
+
+        clone
+    }
+}
+
+/// The cached factors don't affect the result of any operation, so they're ignored.
+impl PartialEq for Blinding {
+    fn eq(&self, other: &Self) -> bool {
+        self.enabled == other.enabled
+    }
+}
+
+impl Eq for Blinding {}
+
+impl RsaOaepEncrypt for RsaPublicKey {
+    fn encrypt<R: RsaCsprng + ?Sized, M: MaskGenerationFunction>(
+        &self,
+        rng: &mut R,
+        params: &OaepParams<M>,
+        message: impl AsRef<[u8]>,
+        label: Option<&[u8]>,
+    ) -> Result<Vec<u8>, RsaError> {
+        let mut seed = vec![0u8; params.hash.output_len()];
+        rng.fill_bytes(&mut seed);
+
+        let em = oaep_encode(
+            params.hash,
+            &params.mgf,
+            message.as_ref(),
+            label,
+            &seed,
+            self.modulus_len(),
+        )?;
+
+        self.crypt_with_bytes(&em)
+    }
+}
+
+impl RsaOaepDecrypt for RsaPrivateKey {
+    fn decrypt<M: MaskGenerationFunction>(
+        &self,
+        params: &OaepParams<M>,
+        ciphertext: impl AsRef<[u8]>,
+        label: Option<&[u8]>,
+    ) -> Result<Vec<u8>, RsaError> {
+        let ciphertext = ciphertext.as_ref();
+        let k = self.modulus_len();
+        let h_len = params.hash.output_len();
+
+        if ciphertext.len() != k || k < 2 * h_len + 2 {
+            return Err(decryption_error());
+        }
+
+        let em = Zeroizing::new(
+            self.crypt_with_bytes(ciphertext)
+                .map_err(|_| decryption_error())?,
+        );
+
+        oaep_decode(params.hash, &params.mgf, &em, label)
+    }
+}
+
+impl RsaPkcs1v15Encrypt for RsaPublicKey {
+    fn encrypt_pkcs1v15<R: RsaCsprng + ?Sized>(
+        &self,
+        rng: &mut R,
+        message: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, RsaError> {
+        let message = message.as_ref();
+        let k = self.modulus_len();
+
+        if k < PKCS1V15_MIN_PADDING_LEN + 3 || message.len() > k - PKCS1V15_MIN_PADDING_LEN - 3 {
+            return Err(RsaError::new(
+                RsaErrorKind::CryptographyError,
+                String::from("message too long"),
+            ));
+        }
+
+        // PS consists of pseudo-randomly generated nonzero octets
+        let mut ps = vec![0u8; k - message.len() - 3];
+        rng.fill_bytes(&mut ps);
+        for b in ps.iter_mut() {
+            while *b == 0x00 {
+                let mut redraw = [0u8; 1];
+                rng.fill_bytes(&mut redraw);
+                *b = redraw[0];
+            }
+        }
+
+        // EM = 0x00 || 0x02 || PS || 0x00 || M
+        let mut em = Vec::with_capacity(k);
+        em.extend([0x00, 0x02]);
+        em.extend(ps);
+        em.push(0x00);
+        em.extend_from_slice(message);
+
+        self.crypt_with_bytes(&em)
+    }
+}
+
+impl RsaPkcs1v15Decrypt for RsaPrivateKey {
+    fn decrypt_pkcs1v15(&self, ciphertext: impl AsRef<[u8]>) -> Result<Vec<u8>, RsaError> {
+        let ciphertext = ciphertext.as_ref();
+        let k = self.modulus_len();
+
+        if ciphertext.len() != k || k < PKCS1V15_MIN_PADDING_LEN + 3 {
+            return Err(decryption_error());
+        }
+
+        let em = Zeroizing::new(
+            self.crypt_with_bytes(ciphertext)
+                .map_err(|_| decryption_error())?,
+        );
+        let d = Zeroizing::new(i2osp(&self.private_exponent, k).map_err(|_| decryption_error())?);
+
+        let kdk = Zeroizing::new(implicit_rejection_kdk(&d, ciphertext));
+        let synthetic = Zeroizing::new(implicit_rejection_prf(&kdk, b"message", k * 8));
+        let synthetic_len = implicit_rejection_length(&kdk, k);
+
+        let mut valid = em[0].ct_eq(&0x00) & em[1].ct_eq(&0x02);
+
+        // find the 0x00 separating PS from M without branching on the contents
+        let mut looking = Choice::from(1);
+        let mut separator: u32 = 0;
+        for (i, b) in em.iter().enumerate().skip(2) {
+            let is_zero = b.ct_eq(&0x00);
+
+            separator.conditional_assign(&(i as u32), looking & is_zero);
+            looking &= !is_zero;
+        }
+        valid &= !looking;
+        valid &= !separator.ct_lt(&(PKCS1V15_MIN_PADDING_LEN as u32 + 2));
+
+        let mut message_index = k as u32 - synthetic_len;
+        message_index.conditional_assign(&(separator + 1), valid);
+
+        let out = Zeroizing::new(
+            em.iter()
+                .zip(synthetic.iter())
+                .map(|(real, fake)| u8::conditional_select(fake, real, valid))
+                .collect::<Vec<u8>>(),
+        );
+
+        Ok(out[message_index as usize..].to_vec())
+    }
+}
+
+/// EME-OAEP encoding, as described in step 2 of
+/// [RSAES-OAEP-ENCRYPT](https://www.rfc-editor.org/rfc/rfc8017#section-7.1.1).
+///
+/// `seed` must be `hLen` octets long, and `k` is the length of the modulus in octets.
+fn oaep_encode(
+    hash: HashType,
+    mgf: &dyn MaskGenerationFunction,
+    message: &[u8],
+    label: Option<&[u8]>,
+    seed: &[u8],
+    k: usize,
+) -> Result<Vec<u8>, RsaError> {
+    let h_len = hash.output_len();
+
+    if k < 2 * h_len + 2 || message.len() > k - 2 * h_len - 2 {
+        return Err(RsaError::new(
+            RsaErrorKind::CryptographyError,
+            String::from("message too long"),
+        ));
+    }
+
+    // DB = lHash || PS || 0x01 || M
+    let mut db = hash.digest(label.unwrap_or_default());
+    db.resize(k - h_len - message.len() - 2, 0x00);
+    db.push(0x01);
+    db.extend_from_slice(message);
+
+    let db_mask = mgf.generate_mask(seed, k - h_len - 1)?;
+    xor_in_place(&mut db, &db_mask);
+
+    let seed_mask = mgf.generate_mask(&db, h_len)?;
+    let mut masked_seed = seed.to_vec();
+    xor_in_place(&mut masked_seed, &seed_mask);
+
+    // EM = 0x00 || maskedSeed || maskedDB
+    let mut em = Vec::with_capacity(k);
+    em.push(0x00);
+    em.extend(masked_seed);
+    em.extend(db);
+
+    Ok(em)
+}
+
+/// EME-OAEP decoding, as described in step 3 of
+/// [RSAES-OAEP-DECRYPT](https://www.rfc-editor.org/rfc/rfc8017#section-7.1.2).
+///
+/// The checks on the encoded message are made in constant time, and are only
+/// acted upon once all of them have been made.
+fn oaep_decode(
+    hash: HashType,
+    mgf: &dyn MaskGenerationFunction,
+    em: &[u8],
+    label: Option<&[u8]>,
+) -> Result<Vec<u8>, RsaError> {
+    let h_len = hash.output_len();
+    let l_hash = hash.digest(label.unwrap_or_default());
+
+    let (masked_seed, masked_db) = em[1..].split_at(h_len);
+
+    let seed_mask = mgf
+        .generate_mask(masked_db, h_len)
+        .map_err(|_| decryption_error())?;
+    let mut seed = Zeroizing::new(masked_seed.to_vec());
+    xor_in_place(&mut seed, &seed_mask);
+
+    let db_mask = mgf
+        .generate_mask(&seed, em.len() - h_len - 1)
+        .map_err(|_| decryption_error())?;
+    let mut db = Zeroizing::new(masked_db.to_vec());
+    xor_in_place(&mut db, &db_mask);
+
+    let (l_hash_prime, rest) = db.split_at(h_len);
+
+    let mut valid = em[0].ct_eq(&0x00) & l_hash.as_slice().ct_eq(l_hash_prime);
+
+    // scan PS || 0x01 || M for the separator without branching on the contents
+    let mut looking = Choice::from(1);
+    let mut separator: u32 = 0;
+    for (i, b) in rest.iter().enumerate() {
+        let is_zero = b.ct_eq(&0x00);
+        let is_one = b.ct_eq(&0x01);
+
+        separator.conditional_assign(&(i as u32), looking & is_one);
+        valid &= !looking | is_zero | is_one;
+        looking &= !is_one;
+    }
+    valid &= !looking;
+
+    if !bool::from(valid) {
+        return Err(decryption_error());
+    }
+
+    Ok(rest[separator as usize + 1..].to_vec())
+}
+
+/// Derives the key-derivation key used for implicit rejection:
+/// `KDK = HMAC-SHA256(SHA256(I2OSP(d, k)), C)`.
+fn implicit_rejection_kdk(d: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let key = Sha256::digest(d);
+
+    let mut mac = HmacSha256::new_from_slice(&key).expect("HMAC accepts keys of any length");
+    mac.update(ciphertext);
+
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The pseudo-random function used for implicit rejection. Produces `bit_len / 8` octets
+/// by concatenating `HMAC-SHA256(KDK, I2OSP(i, 2) || label || I2OSP(bit_len, 2))` for `i = 0, 1, ...`
+fn implicit_rejection_prf(kdk: &[u8], label: &[u8], bit_len: usize) -> Vec<u8> {
+    let out_len = bit_len / 8;
+    let mut out = Vec::with_capacity(out_len + 32);
+
+    let mut i: u16 = 0;
+    while out.len() < out_len {
+        let mut mac = HmacSha256::new_from_slice(kdk).expect("HMAC accepts keys of any length");
+        mac.update(&i.to_be_bytes());
+        mac.update(label);
+        mac.update(&(bit_len as u16).to_be_bytes());
+
+        out.extend(mac.finalize().into_bytes());
+        i += 1;
+    }
+
+    out.truncate(out_len);
+    out
+}
+
+/// Picks the length of the synthetic message: the last of the candidate lengths
+/// (masked to the smallest covering power of two) that is below the maximum message length.
+fn implicit_rejection_length(kdk: &[u8], k: usize) -> u32 {
+    let candidates = implicit_rejection_prf(kdk, b"length", IMPLICIT_REJECTION_CANDIDATES * 16);
+
+    let max_len = (k - PKCS1V15_MIN_PADDING_LEN - 2) as u32;
+    let mask = u32::MAX >> max_len.leading_zeros();
+
+    let mut synthetic_len: u32 = 0;
+    for candidate in candidates.chunks_exact(2) {
+        let candidate = u16::from_be_bytes([candidate[0], candidate[1]]) as u32 & mask;
+        synthetic_len.conditional_assign(&candidate, candidate.ct_lt(&max_len));
+    }
+
+    synthetic_len
+}
+
+pub(crate) fn xor_in_place(data: &mut [u8], mask: &[u8]) {
+    for (d, m) in data.iter_mut().zip(mask) {
+        *d ^= m;
+    }
+}
+
+fn decryption_error() -> RsaError {
+    RsaError::new(
+        RsaErrorKind::CryptographyError,
+        String::from("decryption error"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mask::Shake256Mgf;
+    use crate::util::{carmichael_totient, from_hex};
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use zeroize::Zeroize;
+
+    fn key_from_primes(p: &str, q: &str, e: u32) -> (RsaPublicKey, RsaPrivateKey) {
+        let p = os2ip(&from_hex(p));
+        let q = os2ip(&from_hex(q));
+        let e = BigUint::from(e);
+        let n = &p * &q;
+
+        let d = e.modinv(&carmichael_totient(&p, &q)).unwrap();
+        let dp = &d % (&p - 1u32);
+        let dq = &d % (&q - 1u32);
+        let qinv = q.modinv(&p).unwrap();
+
+        (
+            RsaPublicKey::new(e.clone(), n.clone()),
+            RsaPrivateKey::new(0, n, e, d, p, q, dp, dq, qinv),
+        )
+    }
+
+    // Worked example from the PKCS #1 v2.1 test vectors (oaep-int.txt),
+    // which uses SHA-1 for both the label hash and MGF1.
+    const OAEP_INT_P: &str = "
+        ee cf ae 81 b1 b9 b3 c9 08 81 0b 10 a1 b5 60 01 99 eb 9f 44 ae f4 fd a4 93 b8 1a 9e 3d 84 f6 32
+        12 4e f0 23 6e 5d 1e 3b 7e 28 fa e7 aa 04 0a 2d 5b 25 21 76 45 9d 1f 39 75 41 ba 2a 58 fb 65 99";
+    const OAEP_INT_Q: &str = "
+        c9 7f b1 f0 27 f4 53 f6 34 12 33 ea aa d1 d9 35 3f 6c 42 d0 88 66 b1 d0 5a 0f 20 35 02 8b 9d 86
+        98 40 b4 16 66 b4 2e 92 ea 0d a3 b4 32 04 b5 cf ce 33 52 52 4d 04 16 a5 a4 41 e7 00 af 46 15 03";
+    const OAEP_INT_E: u32 = 0x11;
+    const OAEP_INT_MESSAGE: &str = "d4 36 e9 95 69 fd 32 a7 c8 a0 5b bc 90 d3 2c 49";
+    const OAEP_INT_SEED: &str = "aa fd 12 f6 59 ca e6 34 89 b4 79 e5 07 6d de c2 f0 6c b5 8f";
+    const OAEP_INT_CIPHERTEXT: &str = "
+        12 53 e0 4d c0 a5 39 7b b4 4a 7a b8 7e 9b f2 a0 39 a3 3d 1e 99 6f c8 2a 94 cc d3 00 74 c9 5d f7
+        63 72 20 17 06 9e 52 68 da 5d 1c 0b 4f 87 2c f6 53 c1 1d f8 23 14 a6 79 68 df ea e2 8d ef 04 bb
+        6d 84 b1 c3 1d 65 4a 19 70 e5 78 3b d6 eb 96 a0 24 c2 ca 2f 4a 90 fe 9f 2e f5 c9 c1 40 e5 bb 48
+        da 95 36 ad 87 00 c8 4f c9 13 0a de a7 4e 55 8d 51 a7 4d df 85 d8 b5 0d e9 68 38 d6 06 3e 09 55";
+
+    /// A key from the PKCS #1 v2.1 OAEP test vectors (oaep-vect.txt), with `e = 65537`,
+    /// and some of the messages encrypted under it. SHA-1 is used for both the label hash and MGF1.
+    struct OaepVectorKey {
+        p: &'static str,
+        q: &'static str,
+        cases: &'static [OaepVector],
+    }
+
+    struct OaepVector {
+        message: &'static str,
+        seed: &'static str,
+        ciphertext: &'static str,
+    }
+
+    // examples 1, 2, 5, 9 and 10, with 1024, 1025, 1028, 1536 and 2048-bit moduli
+    const OAEP_VECTORS: &[OaepVectorKey] = &[
+        OaepVectorKey {
+            p: "
+                d3 27 37 e7 26 7f fe 13 41 b2 d5 c0 d1 50 a8 1b 58 6f b3 13 2b ed 2f 8d 52 62 86 4a 9c b9 f3 0a
+                f3 8b e4 48 59 8d 41 3a 17 2e fb 80 2c 21 ac f1 c1 1c 52 0c 2f 26 a4 71 dc ad 21 2e ac 7c a3 9d",
+            q: "
+                cc 88 53 d1 d5 4d a6 30 fa c0 04 f4 71 f2 81 c7 b8 98 2d 82 24 a4 90 ed be b3 3d 3e 3d 5c c9 3c
+                47 65 70 3d 1d d7 91 64 2f 1f 11 6a 0d d8 52 be 24 19 b2 af 72 bf e9 a0 30 e8 60 b0 28 8b 5d 77",
+            cases: &[
+                OaepVector {
+                    message: "66 28 19 4e 12 07 3d b0 3b a9 4c da 9e f9 53 23 97 d5 0d ba 79 b9 87 00 4a fe fe 34",
+                    seed: "18 b7 76 ea 21 06 9d 69 77 6a 33 e9 6b ad 48 e1 dd a0 a5 ef",
+                    ciphertext: "
+                        35 4f e6 7b 4a 12 6d 5d 35 fe 36 c7 77 79 1a 3f 7b a1 3d ef 48 4e 2d 39 08 af f7 22 fa d4 68 fb
+                        21 69 6d e9 5d 0b e9 11 c2 d3 17 4f 8a fc c2 01 03 5f 7b 6d 8e 69 40 2d e5 45 16 18 c2 1a 53 5f
+                        a9 d7 bf c5 b8 dd 9f c2 43 f8 cf 92 7d b3 13 22 d6 e8 81 ea a9 1a 99 61 70 e6 57 a0 5a 26 64 26
+                        d9 8c 88 00 3f 84 77 c1 22 70 94 a0 d9 fa 1e 8c 40 24 30 9c e1 ec cc b5 21 00 35 d4 7a c7 2e 8a",
+                },
+                OaepVector {
+                    message: "26 52 10 50 84 42 71",
+                    seed: "e4 ec 09 82 c2 33 6f 3a 67 7f 6a 35 61 74 eb 0c e8 87 ab c2",
+                    ciphertext: "
+                        42 ce e2 61 7b 1e ce a4 db 3f 48 29 38 6f bd 61 da fb f0 38 e1 80 d8 37 c9 63 66 df 24 c0 97 b4
+                        ab 0f ac 6b df 59 0d 82 1c 9f 10 64 2e 68 1a d0 5b 8d 78 b3 78 c0 f4 6c e2 fa d6 3f 74 e0 ad 3d
+                        f0 6b 07 5d 7e b5 f5 63 6f 8d 40 3b 90 59 ca 76 1b 5c 62 bb 52 aa 45 00 2e a7 0b aa ce 08 de d2
+                        43 b9 d8 cb d6 2a 68 ad e2 65 83 2b 56 56 4e 43 a6 fa 42 ed 19 9a 09 97 69 74 2d f1 53 9e 82 55",
+                },
+            ],
+        },
+        OaepVectorKey {
+            p: "
+                01 59 db de 04 a3 3e f0 6f b6 08 b8 0b 19 0f 4d 3e 22 bc c1 3a c8 e4 a0 81 03 3a bf a4 16 ed b0
+                b3 38 aa 08 b5 73 09 ea 5a 52 40 e7 dc 6e 54 37 8c 69 41 4c 31 d9 7d db 1f 40 6d b3 76 9c c4 1a
+                43",
+            q: "
+                01 2b 65 2f 30 40 3b 38 b4 09 95 fd 6f f4 1a 1a cc 8a da 70 37 32 36 b7 20 2d 39 b2 ee 30 cf b4
+                6d b0 95 11 f6 f3 07 cc 61 cc 21 60 6c 18 a7 5b 8a 62 f8 22 df 03 1b a0 df 0d af d5 50 6f 56 8b
+                d7",
+            cases: &[
+                OaepVector {
+                    message: "8f f0 0c aa 60 5c 70 28 30 63 4d 9a 6c 3d 42 c6 52 b5 8c f1 d9 2f ec 57 0b ee e7",
+                    seed: "8c 40 7b 5e c2 89 9e 50 99 c5 3e 8c e7 93 bf 94 e7 1b 17 82",
+                    ciphertext: "
+                        01 81 af 89 22 b9 fc b4 d7 9d 92 eb e1 98 15 99 2f c0 c1 43 9d 8b cd 49 13 98 a0 f4 ad 3a 32 9a
+                        5b d9 38 55 60 db 53 26 83 c8 b7 da 04 e4 b1 2a ed 6a ac df 47 1c 34 c9 cd a8 91 ad dc c2 df 34
+                        56 65 3a a6 38 2e 9a e5 9b 54 45 52 57 eb 09 9d 56 2b be 10 45 3f 2b 6d 13 c5 9c 02 e1 0f 1f 8a
+                        bb 5d a0 d0 57 09 32 da cf 2d 09 01 db 72 9d 0f ef cc 05 4e 70 96 8e a5 40 c8 1b 04 bc ae fe 72
+                        0e",
+                },
+                OaepVector {
+                    message: "8a 7f b3 44 c8 b6 cb 2c f2 ef 1f 64 3f 9a 32 18 f6 e1 9b ba 89 c0",
+                    seed: "4c 45 cf 4d 57 c9 8e 3d 6d 20 95 ad c5 1c 48 9e b5 0d ff 84",
+                    ciphertext: "
+                        01 08 39 ec 20 c2 7b 90 52 e5 5b ef b9 b7 7e 6f c2 6e 90 75 d7 a5 43 78 c6 46 ab df 51 e4 45 bd
+                        57 15 de 81 78 9f 56 f1 80 3d 91 70 76 4a 9e 93 cb 78 79 86 94 02 3e e7 39 3c e0 4b c5 d8 f8 c5
+                        a5 2c 17 1d 43 83 7e 3a ca 62 f6 09 eb 0a a5 ff b0 96 0e f0 41 98 dd 75 4f 57 f7 fb e6 ab f7 65
+                        cf 11 8b 4c a4 43 b2 3b 5a ab 26 6f 95 23 26 ac 45 81 10 06 44 32 5f 8b 72 1a cd 5d 04 ff 14 ef
+                        3a",
+                },
+            ],
+        },
+        OaepVectorKey {
+            p: "
+                03 b0 d3 96 2f 6d 17 54 9c bf ca 11 29 43 48 dc f0 e7 e3 9f 8c 2b c6 82 4f 21 64 b6 06 d6 87 86
+                0d ae 1e 63 23 93 cf ed f5 13 22 82 29 06 9e 2f 60 e4 ac d7 e6 33 a4 36 06 3f 82 38 5f 48 99 37
+                07",
+            q: "
+                02 e4 c3 2e 2f 51 72 69 b7 07 23 09 f0 0c 0e 31 36 5f 7c e2 8b 23 6b 82 91 2d f2 39 ab f3 95 72
+                cf 0e d6 04 b0 29 82 e5 35 64 c5 2d 6a 05 39 7d e5 c0 52 a2 fd dc 14 1e f7 18 98 36 34 6a eb 33
+                1f",
+            cases: &[
+                OaepVector {
+                    message: "af 71 a9 01 e3 a6 1d 31 32 f0 fc 1f db 47 4f 9e a6 57 92 57 ff c2 4d 16 41 70 14 5b 3d bd e8",
+                    seed: "44 c9 2e 28 3f 77 b9 49 9c 60 3d 96 36 60 c8 7d 2f 93 94 61",
+                    ciphertext: "
+                        03 60 46 a4 a4 7d 9e d3 ba 9a 89 13 9c 10 50 38 eb 74 92 b0 5a 5d 68 bf d5 3a cc ff 45 97 f7 a6
+                        86 51 b4 7b 4a 46 27 d9 27 e4 85 ee d7 b4 56 64 20 e8 b4 09 87 9e 5d 60 6e ae 25 1d 22 a5 df 79
+                        9f 79 20 bf c1 17 b9 92 57 2a 53 b1 26 31 46 bc ea 03 38 5c c5 e8 53 c9 a1 01 c8 c3 e1 bd a3 1a
+                        51 98 07 49 6c 6c b5 e5 ef b4 08 82 3a 35 2b 8f a0 66 1f b6 64 ef ad d5 93 de b9 9f ff 5e d0 00
+                        e5",
+                },
+                OaepVector {
+                    message: "54 1e 37 b6 8b 6c 88 72 b8 4c 02",
+                    seed: "d9 fb a4 5c 96 f2 1e 6e 26 d2 9e b2 cd cb 65 85 be 9c b3 41",
+                    ciphertext: "
+                        08 c3 6d 4d da 33 42 3b 2e d6 83 0d 85 f6 41 1b a1 dc f4 70 a1 fa e0 eb ef ee 7c 08 9f 25 6c ef
+                        74 cb 96 ea 69 c3 8f 60 f3 9a be e4 41 29 bc b4 c9 2d e7 f7 97 62 3b 20 07 4e 3d 9c 28 99 70 1e
+                        d9 07 1e 1e fa 0b dd 84 d4 c3 e5 13 03 02 d8 f0 24 0b ab a4 b8 4a 71 cc 03 2f 22 35 a5 ff 0f ae
+                        27 7c 3e 8f 91 12 be f4 4c 9a e2 0d 17 5f c9 a4 05 8b fc 93 0b a3 1b 02 e2 e4 f4 44 48 37 10 f2
+                        4a",
+                },
+            ],
+        },
+        OaepVectorKey {
+            p: "
+                fc 8d 6c 04 be c4 eb 9a 81 92 ca 79 00 cb e5 36 e2 e8 b5 19 de cf 33 b2 45 97 98 c6 90 9d f4 f1
+                76 db 7d 23 19 0f c7 2b 88 65 a7 18 af 89 5f 1b cd 91 45 29 80 27 42 3b 60 5e 70 a4 7c f5 83 90
+                a8 c3 e8 8f c8 c4 8e 8b 32 e3 da 21 0d fb e3 e8 81 ea 56 74 b6 a3 48 c2 1e 93 f9 e5 5e a6 5e fd",
+            q: "
+                d2 00 d4 5e 78 8a ac ea 60 6a 40 1d 04 60 f8 7d d5 c1 02 7e 12 dc 1a 0d 75 86 e8 93 9d 9c f7 89
+                b4 0f 51 ac 04 42 96 1d e7 d2 1c c2 1e 05 c8 31 55 c1 f2 aa 91 93 38 7c fd f9 56 cb 48 d1 53 ba
+                27 04 06 f9 bb ba 53 7d 49 87 d9 e2 f9 94 2d 7a 14 cb ff fe a7 4f ec dd a9 28 d2 3e 25 9f 5e e1",
+            cases: &[
+                OaepVector {
+                    message: "
+                        f7 35 fd 55 ba 92 59 2c 3b 52 b8 f9 c4 f6 9a aa 1c be f8 fe 88 ad d0 95 59 54 12 46 7f 9c f4 ec
+                        0b 89 6c 59 ed a1 62 10 e7 54 9c 8a bb 10 cd bc 21 a1 2e c9 b6 b5 b8 fd 2f 10 39 9e b6",
+                    seed: "8e c9 65 f1 34 a3 ec 99 31 e9 2a 1c a0 dc 81 69 d5 ea 70 5c",
+                    ciphertext: "
+                        26 7b cd 11 8a ca b1 fc 8b a8 1c 85 d7 30 03 cb 86 10 fa 55 c1 d9 7d a8 d4 8a 7c 7f 06 89 6a 4d
+                        b7 51 aa 28 42 55 b9 d3 6a d6 5f 37 65 3d 82 9f 1b 37 f9 7b 80 01 94 25 45 b2 fc 2c 55 a7 37 6c
+                        a7 a1 be 4b 17 60 c8 e0 5a 33 e5 aa 25 26 b8 d9 8e 31 70 88 e7 83 4c 75 5b 2a 59 b1 26 31 a1 82
+                        c0 5d 5d 43 ab 17 79 26 4f 84 56 f5 15 ce 57 df df 51 2d 54 93 da b7 b7 33 8d c4 b7 d7 8d b9 c0
+                        91 ac 3b af 53 7a 69 fc 7f 54 9d 97 9f 0e ff 9a 94 fd a4 16 9b d4 d1 d1 9a 69 c9 9e 33 c3 b5 54
+                        90 d5 01 b3 9b 1e da e1 18 ff 67 93 a1 53 26 15 84 d3 a5 f3 9f 6e 68 2e 3d 17 c8 cd 12 61 fa 72",
+                },
+                OaepVector {
+                    message: "b6 b2 8e a2 19 8d 0c 10 08 bc 64",
+                    seed: "23 aa de 0e 1e 08 bb 9b 9a 78 d2 30 2a 52 f9 c2 1b 2e 1b a2",
+                    ciphertext: "
+                        43 8c c7 dc 08 a6 8d a2 49 e4 25 05 f8 57 3b a6 0e 2c 27 73 d5 b2 90 f4 cf 9d ff 71 8e 84 20 81
+                        c3 83 e6 70 24 a0 f2 95 94 ea 98 7b 9d 25 e4 b7 38 f2 85 97 0d 19 5a bb 3a 8c 80 54 e3 d7 9d 6b
+                        9c 9a 83 27 ba 59 6f 12 59 e2 71 26 67 47 66 90 7d 8d 58 2f f3 a8 47 61 54 92 9a db 1e 6d 12 35
+                        b2 cc b4 ec 8f 66 3b a9 cc 67 0a 92 be bd 85 3c 8d bf 69 c6 43 6d 01 6f 61 ad d8 36 e9 47 32 45
+                        04 34 20 7f 9f d4 c4 3d ec 2a 12 a9 58 ef a0 1e fe 26 69 89 9b 5e 60 4c 25 5c 55 fb 71 66 de 55
+                        89 e3 69 59 7b b0 91 68 c0 6d d5 db 17 7e 06 a1 74 0e b2 d5 c8 2f ae ca 6d 92 fc ee 99 31 ba 9f",
+                },
+            ],
+        },
+        OaepVectorKey {
+            p: "
+                ec f5 ae cd 1e 55 15 ff fa cb d7 5a 28 16 c6 eb f4 90 18 cd fb 46 38 e1 85 d6 6a 73 96 b6 f8 09
+                0f 80 18 c7 fd 95 cc 34 b8 57 dc 17 f0 cc 65 16 bb 13 46 ab 4d 58 2c ad ad 7b 41 03 35 23 87 b7
+                03 38 d0 84 04 7c 9d 95 39 b6 49 62 04 b3 dd 6e a4 42 49 92 07 be c0 1f 96 42 87 ff 63 36 c3 98
+                46 58 33 68 46 f5 6e 46 86 18 81 c1 02 33 d2 17 6b f1 5a 5e 96 dd c7 80 bc 86 8a a7 7d 3c e7 69",
+            q: "
+                bc 46 c4 64 fc 6a c4 ca 78 3b 0e b0 8a 3c 84 1b 77 2f 7e 9b 2f 28 ba bd 58 8a e8 85 e1 a0 c6 1e
+                48 58 a0 fb 25 ac 29 99 90 f3 5b e8 51 64 c2 59 ba 11 75 cd d7 19 27 07 13 51 84 99 2b 6c 29 b7
+                46 dd 0d 2c ab e1 42 83 5f 7d 14 8c c1 61 52 4b 4a 09 94 6d 48 b8 28 47 3f 1c e7 6b 6c b6 88 6c
+                34 5c 03 e0 5f 41 d5 1b 5c 3a 90 a3 f2 40 73 c7 d7 4a 4f e2 5d 9c f2 1c 75 96 0f 3f c3 86 31 83",
+            cases: &[
+                OaepVector {
+                    message: "8b ba 6b f8 2a 6c 0f 86 d5 f1 75 6e 97 95 68 70 b0 89 53 b0 6b 4e b2 05 bc 16 94 ee",
+                    seed: "47 e1 ab 71 19 fe e5 6c 95 ee 5e aa d8 6f 40 d0 aa 63 bd 33",
+                    ciphertext: "
+                        53 ea 5d c0 8c d2 60 fb 3b 85 85 67 28 7f a9 15 52 c3 0b 2f eb fb a2 13 f0 ae 87 70 2d 06 8d 19
+                        ba b0 7f e5 74 52 3d fb 42 13 9d 68 c3 c5 af ee e0 bf e4 cb 79 69 cb f3 82 b8 04 d6 e6 13 96 14
+                        4e 2d 0e 60 74 1f 89 93 c3 01 4b 58 b9 b1 95 7a 8b ab cd 23 af 85 4f 4c 35 6f b1 66 2a a7 2b fc
+                        c7 e5 86 55 9d c4 28 0d 16 0c 12 67 85 a7 23 eb ee be ff 71 f1 15 94 44 0a ae f8 7d 10 79 3a 87
+                        74 a2 39 d4 a0 4c 87 fe 14 67 b9 da f8 52 08 ec 6c 72 55 79 4a 96 cc 29 14 2f 9a 8b d4 18 e3 c1
+                        fd 67 34 4b 0c d0 82 9d f3 b2 be c6 02 53 19 62 93 c6 b3 4d 3f 75 d3 2f 21 3d d4 5c 62 73 d5 05
+                        ad f4 cc ed 10 57 cb 75 8f c2 6a ee fa 44 12 55 ed 4e 64 c1 99 ee 07 5e 7f 16 64 61 82 fd b4 64
+                        73 9b 68 ab 5d af f0 e6 3e 95 52 01 68 24 f0 54 bf 4d 3c 8c 90 a9 7b b6 b6 55 32 84 eb 42 9f cc",
+                },
+                OaepVector {
+                    message: "
+                        ea f1 a7 3a 1b 0c 46 09 53 7d e6 9c d9 22 8b bc fb 9a 8c a8 c6 c3 ef af 05 6f e4 a7 f4 63 4e d0
+                        0b 7c 39 ec 69 22 d7 b8 ea 2c 04 eb ac",
+                    seed: "9f 47 dd f4 2e 97 ee a8 56 a9 bd bc 71 4e b3 ac 22 f6 eb 32",
+                    ciphertext: "
+                        2d 20 7a 73 43 2a 8f b4 c0 30 51 b3 f7 3b 28 a6 17 64 09 8d fa 34 c4 7a 20 99 5f 81 15 aa 68 16
+                        67 9b 55 7e 82 db ee 58 49 08 c6 e6 97 82 d7 de b3 4d bd 65 af 06 3d 57 fc a7 6a 5f d0 69 49 2f
+                        d6 06 8d 99 84 d2 09 35 05 65 a6 2e 5c 77 f2 30 38 c1 2c b1 0c 66 34 70 9b 54 7c 46 f6 b4 a7 09
+                        bd 85 ca 12 2d 74 46 5e f9 77 62 c2 97 63 e0 6d bc 7a 9e 73 8c 78 bf ca 01 02 dc 5e 79 d6 5b 97
+                        3f 28 24 0c aa b2 e1 61 a7 8b 57 d2 62 45 7e d8 19 5d 53 e3 c7 ae 9d a0 21 88 3c 6d b7 c2 4a fd
+                        d2 32 2e ac 97 2a d3 c3 54 c5 fc ef 1e 14 6c 3a 02 90 fb 67 ad f0 07 06 6e 00 42 8d 2c ec 18 ce
+                        58 f9 32 86 98 de fe f4 b2 eb 5e c7 69 18 fd e1 c1 98 cb b3 8b 7a fc 67 62 6a 9a ef ec 43 22 bf
+                        d9 0d 25 63 48 1c 9a 22 1f 78 c8 27 2c 82 d1 b6 2a b9 14 e1 c6 9f 6a f6 ef 30 ca 52 60 db 4a 46",
+                },
+            ],
+        },
+    ];
+
+    const SHA1_PARAMS: OaepParams = OaepParams {
+        hash: HashType::Sha1,
+        mgf: Mgf1 {
+            hash: HashType::Sha1,
+        },
+    };
+
+    #[test]
+    fn test_crypt_with_bytes_fixed_length() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let k = pk.modulus_len();
+
+        // leading zero octets must survive the round trip
+        let mut message = vec![0x00; k];
+        message[k - 1] = 0x02;
+
+        let c = pk.crypt_with_bytes(&message).unwrap();
+        assert_eq!(c.len(), k);
+        assert_eq!(sk.crypt_with_bytes(&c).unwrap(), message);
+
+        assert_eq!(pk.crypt_with_bytes(&[0x02]).unwrap(), c);
+        assert!(pk.crypt_with_bytes(&[0xFF; 128]).is_err());
+    }
+
+    #[test]
+    fn test_crt_fault_countermeasure() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let m = BigUint::from(42u32);
+        let c = pk.crypt(&m).unwrap();
+
+        // a wrong dP stands in for a fault during the CRT computation,
+        // and would leak p as gcd(m'^e - c, n) if the result were released
+        let mut faulty = sk.clone();
+        faulty.exponent1 += 1u32;
+
+        for blinding in [true, false] {
+            let faulty = faulty.clone().with_blinding(blinding);
+            assert!(faulty.crypt(&c).is_err());
+            assert!(faulty.decrypt_pkcs1v15([0x01; 128]).is_err());
+
+            let recovering = faulty.clone().with_fault_recovery(FaultRecovery::NonCrt);
+            assert_eq!(recovering.crypt(&c).unwrap(), m);
+
+            // if d is wrong too, there is nothing to fall back to
+            let mut broken = recovering.clone();
+            broken.private_exponent += 1u32;
+            assert!(broken.crypt(&c).is_err());
+        }
+
+        assert_eq!(
+            sk.with_fault_recovery(FaultRecovery::NonCrt)
+                .crypt(&c)
+                .unwrap(),
+            m
+        );
+    }
+
+    #[test]
+    fn test_precomputed() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let m = BigUint::from(42u32);
+
+        for _ in 0..3 {
+            let c = pk.crypt(&m).unwrap();
+            assert_eq!(sk.crypt(&c).unwrap(), m);
+        }
+
+        assert_eq!(pk.precomputed.moduli.get().unwrap().len(), 1);
+        assert_eq!(sk.precomputed.moduli.get().unwrap().len(), 3);
+        assert!(pk.clone().precomputed.moduli.get().is_some());
+
+        // values that no longer match the key aren't used
+        let mut other = pk.clone();
+        other.modulus = &pk.modulus + 2u32;
+        assert_eq!(
+            other.crypt(&m).unwrap(),
+            m.modpow(&pk.public_exponent, &other.modulus)
+        );
+
+        let mut sk = sk;
+        sk.zeroize();
+        assert!(sk.precomputed.moduli.get().is_none());
+    }
+
+    #[test]
+    fn test_blinding() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let unblinded = sk.clone().with_blinding(false);
+        assert!(sk.is_blinded());
+        assert!(!unblinded.is_blinded());
+
+        let cached_factors = |sk: &RsaPrivateKey| {
+            let factors = sk.blinding.factors.lock().unwrap();
+            factors
+                .as_ref()
+                .map(|f| (f.blind.clone(), f.unblind.clone(), f.uses))
+        };
+        assert!(cached_factors(&sk).is_none());
+
+        let mut rng = StdRng::seed_from_u64(22);
+        let mut previous: Option<(BigUint, BigUint, u32)> = None;
+
+        // enough operations to refresh the factors a few times
+        for _ in 0..3 * BLINDING_REFRESH_INTERVAL + 1 {
+            let m = rng.gen_biguint_below(&pk.modulus);
+            let c = pk.crypt(&m).unwrap();
+
+            assert_eq!(sk.crypt(&c).unwrap(), m);
+            assert_eq!(unblinded.crypt(&c).unwrap(), m);
+
+            // blind = r^e and unblind = r^(-1), so blind * unblind^e = 1 mod n
+            let (blind, unblind, uses) = cached_factors(&sk).unwrap();
+            assert!(
+                (&blind * unblind.modpow(&pk.public_exponent, &pk.modulus) % &pk.modulus).is_one()
+            );
+
+            // the factors are squared after each use, until they are drawn again
+            match previous {
+                Some((prev_blind, prev_unblind, prev_uses))
+                    if prev_uses < BLINDING_REFRESH_INTERVAL =>
+                {
+                    assert_eq!(uses, prev_uses + 1);
+                    assert_eq!(blind, prev_blind.modpow(&BigUint::from(2u32), &pk.modulus));
+                    assert_eq!(
+                        unblind,
+                        prev_unblind.modpow(&BigUint::from(2u32), &pk.modulus)
+                    );
+                }
+                _ => assert_eq!(uses, 1),
+            }
+            previous = Some((blind, unblind, uses));
+        }
+        assert!(cached_factors(&unblinded).is_none());
+
+        // clones draw their own factors, and compare equal regardless
+        let clone = sk.clone();
+        assert!(cached_factors(&clone).is_none());
+        assert_eq!(clone, sk);
+        assert_ne!(unblinded, sk);
+
+        // factors drawn from the same seeded CSPRNG are the same
+        let c = pk.crypt(&BigUint::from(42u32)).unwrap();
+        let seeded = |seed| {
+            let sk = sk.clone().with_blinding_rng(StdRng::seed_from_u64(seed));
+            assert_eq!(sk.crypt(&c).unwrap(), BigUint::from(42u32));
+            sk
+        };
+        let (a, b, other) = (seeded(22), seeded(22), seeded(23));
+        assert_eq!(cached_factors(&a), cached_factors(&b));
+        assert_ne!(cached_factors(&a), cached_factors(&other));
+
+        // and so are those of their clones
+        let (a, b) = (a.clone(), b.clone());
+        a.crypt(&c).unwrap();
+        b.crypt(&c).unwrap();
+        assert_eq!(cached_factors(&a), cached_factors(&b));
+    }
+
+    #[test]
+    fn test_oaep_encrypt_vector() {
+        let (pk, _) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let k = pk.modulus_len();
+
+        let em = oaep_encode(
+            HashType::Sha1,
+            &Mgf1::new(HashType::Sha1),
+            &from_hex(OAEP_INT_MESSAGE),
+            None,
+            &from_hex(OAEP_INT_SEED),
+            k,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pk.crypt_with_bytes(&em).unwrap(),
+            from_hex(OAEP_INT_CIPHERTEXT)
+        );
+    }
+
+    #[test]
+    fn test_oaep_decrypt_vector() {
+        let (_, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+
+        let m = sk
+            .decrypt(&SHA1_PARAMS, from_hex(OAEP_INT_CIPHERTEXT), None)
+            .unwrap();
+
+        assert_eq!(m, from_hex(OAEP_INT_MESSAGE));
+    }
+
+    #[test]
+    fn test_oaep_vectors() {
+        for key in OAEP_VECTORS {
+            let (pk, sk) = key_from_primes(key.p, key.q, 65537);
+            let k = pk.modulus_len();
+
+            for case in key.cases {
+                let message = from_hex(case.message);
+                let ciphertext = from_hex(case.ciphertext);
+
+                let em = oaep_encode(
+                    HashType::Sha1,
+                    &Mgf1::new(HashType::Sha1),
+                    &message,
+                    None,
+                    &from_hex(case.seed),
+                    k,
+                )
+                .unwrap();
+                assert_eq!(pk.crypt_with_bytes(&em).unwrap(), ciphertext);
+
+                assert_eq!(
+                    sk.decrypt(&SHA1_PARAMS, &ciphertext, None).unwrap(),
+                    message
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_oaep_label_roundtrip() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let mut rng = StdRng::seed_from_u64(0);
+        let params = OaepParams::new(HashType::Sha256, HashType::Sha1);
+        let message = b"attack at dawn";
+
+        let c = pk
+            .encrypt(&mut rng, &params, message, Some(b"label"))
+            .unwrap();
+        assert_eq!(c.len(), pk.modulus_len());
+
+        assert_eq!(sk.decrypt(&params, &c, Some(b"label")).unwrap(), message);
+        assert!(sk.decrypt(&params, &c, Some(b"other label")).is_err());
+        assert!(sk.decrypt(&params, &c, None).is_err());
+    }
+
+    #[test]
+    fn test_oaep_custom_mgf() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let mut rng = StdRng::seed_from_u64(0);
+        let message = b"attack at dawn";
+
+        let params = OaepParams::with_mgf(HashType::Sha256, Shake256Mgf);
+        let c = pk.encrypt(&mut rng, &params, message, None).unwrap();
+        assert_eq!(sk.decrypt(&params, &c, None).unwrap(), message);
+
+        // a mask generation function can also be passed as a trait object
+        let mgf: &dyn MaskGenerationFunction = &Shake256Mgf;
+        let dyn_params = OaepParams::with_mgf(HashType::Sha256, mgf);
+        assert_eq!(sk.decrypt(&dyn_params, &c, None).unwrap(), message);
+
+        let mgf1 = OaepParams::new(HashType::Sha256, HashType::Sha256);
+        assert!(sk.decrypt(&mgf1, &c, None).is_err());
+    }
+
+    #[test]
+    fn test_oaep_message_too_long() {
+        let (pk, _) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // k - 2 * hLen - 2 = 128 - 64 - 2
+        let max = vec![0xAB; 62];
+        assert!(pk
+            .encrypt(&mut rng, &OaepParams::default(), &max, None)
+            .is_ok());
+
+        let too_long = vec![0xAB; 63];
+        assert!(pk
+            .encrypt(&mut rng, &OaepParams::default(), &too_long, None)
+            .is_err());
+    }
+
+    // Produced by OpenSSL 3.x (implicit rejection enabled) using the oaep-int key with
+    // `d = e^-1 mod lambda(n)`. The OAEP ciphertext above doesn't carry valid
+    // PKCS #1 v1.5 padding, so decrypting it yields a synthetic message.
+    const PKCS1V15_SYNTHETIC_MESSAGE: &str = "
+        21 83 da 00 9c 0a fe 5e 2d 8d c6 bd bf 3f ab fb ec 2a d7 c7 20 ab 53 45 ca ce f0 7f b5 7d 0f 25
+        c0 36 cd 52 fd 92 23 da 79 15 e8 75 3e 27 f2 c4 6a 75 e7 c1 b0 67 8d 2b 3d e6 c2 6b a8 8c 54 76";
+    const PKCS1V15_CIPHERTEXT: &str = "
+        34 4e 2a 17 7a 2a 24 f3 85 a2 7f e5 43 25 b6 b5 a1 37 1c f4 3c 87 e0 06 68 9d f2 62 1f 57 e9 4f
+        b4 00 ba 43 a4 b5 0e 2d db fe 00 b1 fa 55 01 ef 2c dd ad 7b ab e2 7a 1a 36 cf ef f8 9f 3a b7 9f
+        9c 19 4b 30 59 bd c4 aa 1f e6 97 91 b3 c6 8b b5 f7 10 01 44 d8 66 d1 19 64 bc 63 91 34 9c 32 0a
+        32 a5 ed 50 94 71 6b 4b 2f 41 42 b8 c8 44 21 d3 6b 85 41 8e bc 76 d7 73 dd 37 ef 4c f1 39 48 9d";
+
+    #[test]
+    fn test_pkcs1v15_decrypt_vector() {
+        let (_, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+
+        let m = sk.decrypt_pkcs1v15(from_hex(PKCS1V15_CIPHERTEXT)).unwrap();
+
+        assert_eq!(m, b"attack at dawn");
+    }
+
+    #[test]
+    fn test_pkcs1v15_implicit_rejection() {
+        let (_, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let c = from_hex(OAEP_INT_CIPHERTEXT);
+
+        let m = sk.decrypt_pkcs1v15(&c).unwrap();
+        assert_eq!(m, from_hex(PKCS1V15_SYNTHETIC_MESSAGE));
+        assert_eq!(sk.decrypt_pkcs1v15(&c).unwrap(), m);
+
+        let mut other = c.clone();
+        other[127] ^= 0x01;
+        assert_ne!(sk.decrypt_pkcs1v15(&other).unwrap(), m);
+
+        assert!(sk.decrypt_pkcs1v15(&c[1..]).is_err());
+    }
+
+    #[test]
+    fn test_pkcs1v15_roundtrip() {
+        let (pk, sk) = key_from_primes(OAEP_INT_P, OAEP_INT_Q, OAEP_INT_E);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // k - 11 = 117
+        for len in [0, 1, 16, 117] {
+            let message = vec![0x5A; len];
+
+            let c = pk.encrypt_pkcs1v15(&mut rng, &message).unwrap();
+            assert_eq!(c.len(), pk.modulus_len());
+            assert_eq!(sk.decrypt_pkcs1v15(&c).unwrap(), message);
+        }
+
+        assert!(pk.encrypt_pkcs1v15(&mut rng, vec![0x5A; 118]).is_err());
     }
 }
@@ -11,6 +11,7 @@ pub enum RsaErrorKind {
     SerialError,
     CryptographyError,
     MaskGenerationFunctionError,
+    ValidationError,
 }
 
 #[derive(Debug)]
@@ -26,6 +27,7 @@ impl Display for RsaError {
             RsaErrorKind::SerialError => "SerialError",
             RsaErrorKind::CryptographyError => "CryptographyError",
             RsaErrorKind::MaskGenerationFunctionError => "MaskGenerationFunctionError",
+            RsaErrorKind::ValidationError => "ValidationError",
         };
 
         write!(f, "{}: {}", err_kind, self.message)
@@ -5,37 +5,188 @@
 //! object, which has various chainable methods one can use to specify various parameters during
 //! key generation.
 
-use num::{BigUint, Integer};
+use core::fmt::{self, Debug, Display};
+use num::{BigUint, Integer, One, Zero};
 use num_bigint::RandBigInt;
-use rand::{rngs::StdRng, CryptoRng, SeedableRng};
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
+use crate::crypto::{Blinding, FaultRecovery, Precomputed, RsaPrimitive};
 use crate::errors::{RsaError, RsaErrorKind}; //RsaOptionsError;
-use crate::util::{carmichael_totient, generate_candidate_prime};
+use crate::serial::rsa_public_key_der_serialize;
+#[cfg(feature = "parallel")]
+use crate::util::generate_prime_parallel;
+use crate::util::{
+    baillie_psw_is_prime, carmichael_totient, carmichael_totient_multi_prime,
+    generate_prime_with_test, zeroize_biguint, BailliePsw, FipsMillerRabin, MillerRabin,
+    PrimalityTest, SieveSize,
+};
 
-/// Each prime factor of RSA modulus `n` is 1024 bits in length.
+/// By default, each prime factor of RSA modulus `n` is 1024 bits in length.
 pub const RSA_PRIME_NUMBER_BIT_LENGTH: u64 = 1024;
 
-/// Each RSA modulus is `2048` bits in length.
+/// By default, each RSA modulus is `2048` bits in length.
 pub const RSA_MODULUS_BIT_LENGTH: usize = 2048;
 
 /// Defined in [RFC8017](https://www.rfc-editor.org/rfc/rfc8017)
 ///
-/// Currently, this library only supports version 0, which means that each RSA modulus
-/// `n = pq`; it is only the product of two primes.
+/// Version 0 keys are two-prime keys, where each RSA modulus `n = pq`;
+/// it is only the product of two primes.
 pub const RSA_VERSION: u8 = 0;
 
+/// Defined in [RFC8017](https://www.rfc-editor.org/rfc/rfc8017)
+///
+/// Version 1 keys are multi-prime keys, where each RSA modulus `n = r_1 * r_2 * ... * r_u`
+/// is the product of `u > 2` primes. The primes after the first two are stored in
+/// `RsaPrivateKey::other_prime_infos`.
+pub const RSA_MULTI_PRIME_VERSION: u8 = 1;
+
+/// The largest number of prime factors of a generated RSA modulus.
+pub const RSA_MAX_PRIME_COUNT: usize = 5;
+
 /// The default number of miller-rabin primality tests to conduct
 pub const DEFAULT_MR_ITERATIONS: usize = 10;
 
-pub trait RsaCsprng: CryptoRng + RandBigInt {}
-impl<T: CryptoRng + RandBigInt> RsaCsprng for T {}
+/// The public exponent used when none is specified.
+pub const DEFAULT_PUBLIC_EXPONENT: u32 = 65537;
+
+/// By default, a specified public exponent can be at most 256 bits long.
+pub const DEFAULT_MAX_EXPONENT_BITS: u64 = 256;
+
+/// The number of random bases `from_components` tries before giving up on factoring the modulus,
+/// as in [SP 800-56B Rev. 2, Appendix C.2](https://csrc.nist.gov/pubs/sp/800/56/b/r2/final).
+const FACTORING_ATTEMPTS: usize = 100;
+
+/// The smallest modulus accepted by `RsaPublicKey::validate` and `RsaPrivateKey::validate`.
+pub const RSA_MIN_MODULUS_BIT_LENGTH: u64 = 1024;
+
+/// The shortest prime the prime generation functions accept: each prime of a
+/// `RSA_MIN_MODULUS_BIT_LENGTH`-bit modulus with `RSA_MAX_PRIME_COUNT` primes.
+pub const RSA_MIN_PRIME_BIT_LENGTH: u64 = RSA_MIN_MODULUS_BIT_LENGTH / RSA_MAX_PRIME_COUNT as u64;
+
+/// FIPS 186-5 only approves moduli of at least 2048 bits.
+pub const FIPS_MIN_MODULUS_BIT_LENGTH: u64 = 2048;
+
+pub trait RsaCsprng: CryptoRng + RngCore + RandBigInt {}
+impl<T: CryptoRng + RngCore + RandBigInt> RsaCsprng for T {}
+
+/// The supported bit lengths of the RSA modulus `n`.
+///
+/// Each prime factor of the modulus is half as long as the modulus.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum KeySize {
+    /// Too weak for real use; only intended for tests.
+    Bits1024,
+    #[default]
+    Bits2048,
+    Bits3072,
+    Bits4096,
+    Bits7680,
+    Bits8192,
+}
+
+impl KeySize {
+    /// The bit length of the RSA modulus.
+    pub fn bits(&self) -> u64 {
+        match self {
+            KeySize::Bits1024 => 1024,
+            KeySize::Bits2048 => 2048,
+            KeySize::Bits3072 => 3072,
+            KeySize::Bits4096 => 4096,
+            KeySize::Bits7680 => 7680,
+            KeySize::Bits8192 => 8192,
+        }
+    }
+
+    /// The bit length of each prime factor of the RSA modulus.
+    pub fn prime_bits(&self) -> u64 {
+        self.bits() / 2
+    }
+}
+
+/// The versions of the deterministic key derivation used by `KeyPairBuilder::from_seed`.
+///
+/// A version pins every choice that affects the derived key, so the same seed and parameters
+/// always derive the same key under the same version. Any change to the derivation gets a new version.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SeedVersion {
+    /// Primes are drawn from a ChaCha20 stream keyed with
+    /// `SHA-256("rs-a seeded keygen v1" || key bits || prime count || e || seed)`,
+    /// where the key bits and the prime count are big-endian `u64`s, and `e` is a big-endian `u32`.
+    /// Candidates are sieved with 512 primes, and tested with Baillie-PSW,
+    /// which doesn't consume the stream.
+    #[default]
+    V1,
+}
+
+/// The parameters of a key derived by `KeyPairBuilder::from_seed`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SeedParams {
+    pub version: SeedVersion,
+    pub key_size: KeySize,
+    /// The number of prime factors of the modulus, between 2 and `RSA_MAX_PRIME_COUNT`.
+    pub prime_count: usize,
+    /// The public exponent, `DEFAULT_PUBLIC_EXPONENT` by default.
+    pub exponent: u32,
+}
+
+impl Default for SeedParams {
+    fn default() -> Self {
+        Self {
+            version: SeedVersion::default(),
+            key_size: KeySize::default(),
+            prime_count: 2,
+            exponent: DEFAULT_PUBLIC_EXPONENT,
+        }
+    }
+}
+
+/// The modulus a private exponent `d` is computed with, as the inverse of `e`.
+///
+/// [RFC8017, Section 3.2](https://www.rfc-editor.org/rfc/rfc8017#section-3.2) allows either;
+/// both give a working key, but different values of `d`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DerivationMode {
+    /// `d = e^(-1) mod lambda(n)`, with the Carmichael totient `lambda(n) = lcm(p - 1, q - 1)`.
+    /// This is what keys generated by this library, and by OpenSSL, use.
+    #[default]
+    Lambda,
+    /// `d = e^(-1) mod phi(n)`, with Euler's totient `phi(n) = (p - 1)(q - 1)`,
+    /// as used by some older implementations.
+    Phi,
+}
+
+/// How the public exponent `e` is chosen during key generation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum PublicExponent {
+    /// A fixed exponent, either specified or `DEFAULT_PUBLIC_EXPONENT`.
+    Fixed(BigUint),
+    /// An exponent drawn at random after the primes are generated.
+    Random,
+}
+
+impl Default for PublicExponent {
+    fn default() -> Self {
+        PublicExponent::Fixed(BigUint::from(DEFAULT_PUBLIC_EXPONENT))
+    }
+}
 
 /// An object used for constructing KeyPairs.
 pub struct KeyPairBuilder {
-    /// An optionally specified public exponent. A commonly chosen exponent for RSA is 65537.
+    /// The public exponent. By default, this is the commonly chosen exponent 65537.
+    ///
+    /// A specified exponent must be odd, greater than 1, and at most `max_exponent_bits` long.
+    /// If it isn't coprime to the Carmichael totient of the generated primes, the primes are
+    /// generated again. Alternatively, a random exponent can be calculated using the `rng`
+    /// also defined in this struct.
+    exponent: PublicExponent,
+
+    /// The maximum bit length of a specified public exponent.
     ///
-    /// If `None` is provided, one will be calculated using the `rng` also defined in this struct.
-    exponent: Option<BigUint>,
+    /// By default, this is `DEFAULT_MAX_EXPONENT_BITS`.
+    max_exponent_bits: u64,
 
     /// An optionally specified RSA modulus, which is defined as a tuple of two primes `p, q`.
     ///
@@ -53,55 +204,259 @@ pub struct KeyPairBuilder {
     /// during the generation of primes.
     ///
     /// By default, the number of iterations is `1` for quick key generation, but it's recommended to increase this value.
+    /// Ignored if a `primality_test` is specified.
     miller_rabin_iterations: usize,
+
+    /// An optionally specified test used to decide whether candidates are prime.
+    ///
+    /// If `None` is provided, the Miller-Rabin Primality Test is conducted
+    /// `miller_rabin_iterations` times. In FIPS mode, the Miller-Rabin Primality Test is always
+    /// conducted at least as many times as FIPS 186-5 requires, and a specified test runs after it.
+    primality_test: Option<Box<dyn PrimalityTest>>,
+
+    /// The number of small primes candidates are sieved with before the primality test.
+    ///
+    /// By default, 512 primes are used.
+    sieve_size: SieveSize,
+
+    /// The number of worker threads searching for each prime.
+    ///
+    /// By default, a single thread is used, so keys from a seeded `rng` are reproducible.
+    #[cfg(feature = "parallel")]
+    threads: usize,
+
+    /// The bit length of the generated RSA modulus. Ignored if a `modulus` is specified.
+    ///
+    /// By default, a 2048-bit modulus is generated.
+    key_size: KeySize,
+
+    /// The number of prime factors of the generated RSA modulus, between 2 and `RSA_MAX_PRIME_COUNT`.
+    /// A modulus with more than 2 primes produces a multi-prime (version 1) key.
+    ///
+    /// By default, the modulus is the product of 2 primes.
+    prime_count: usize,
+
+    /// Whether to generate keys according to [FIPS 186-5](https://csrc.nist.gov/pubs/fips/186-5/final),
+    /// Appendix A.1.3. See `KeyPairBuilder::with_fips_mode`.
+    ///
+    /// By default, FIPS mode is off.
+    fips_mode: bool,
 }
 
 impl Default for KeyPairBuilder {
     fn default() -> Self {
         Self {
-            exponent: None,
+            exponent: PublicExponent::default(),
+            max_exponent_bits: DEFAULT_MAX_EXPONENT_BITS,
             modulus: None,
             rng: None,
             miller_rabin_iterations: DEFAULT_MR_ITERATIONS,
+            primality_test: None,
+            sieve_size: SieveSize::default(),
+            #[cfg(feature = "parallel")]
+            threads: 1,
+            key_size: KeySize::default(),
+            prime_count: 2,
+            fips_mode: false,
         }
     }
 }
 
 impl KeyPairBuilder {
+    /// Creates a builder that deterministically derives a key pair from a 32-byte secret `seed`,
+    /// as described by `params.version`. The same seed and parameters always derive the same key,
+    /// so the seed must be kept as secret as the private key itself.
+    ///
+    /// Changing the rng, primality test, sieve size, exponent, key size, prime count,
+    /// or thread count of the returned builder changes the derived key.
+    pub fn from_seed(seed: [u8; 32], params: &SeedParams) -> Self {
+        let mut builder = Self::default();
+
+        match params.version {
+            SeedVersion::V1 => {
+                let drbg_seed = Sha256::new()
+                    .chain_update(b"rs-a seeded keygen v1")
+                    .chain_update(params.key_size.bits().to_be_bytes())
+                    .chain_update((params.prime_count as u64).to_be_bytes())
+                    .chain_update(params.exponent.to_be_bytes())
+                    .chain_update(seed)
+                    .finalize();
+
+                builder
+                    .with_rng(ChaCha20Rng::from_seed(drbg_seed.into()))
+                    .with_primality_test(Box::new(BailliePsw))
+                    .with_sieve_size(SieveSize::Primes512)
+                    .with_exponent(BigUint::from(params.exponent))
+                    .with_key_size(params.key_size)
+                    .with_prime_count(params.prime_count);
+
+                #[cfg(feature = "parallel")]
+                builder.with_threads(1);
+            }
+        }
+
+        builder
+    }
+
     pub fn with_exponent(&mut self, e: BigUint) -> &mut Self {
-        self.exponent = Some(e);
+        self.exponent = PublicExponent::Fixed(e);
+        self
+    }
+    /// Draws a random public exponent in `[3, lambda(n))` instead of using a fixed one.
+    ///
+    /// Random exponents are usually very large, which makes public key operations
+    /// slow, and many verifiers won't accept them.
+    pub fn with_random_exponent(&mut self) -> &mut Self {
+        self.exponent = PublicExponent::Random;
+        self
+    }
+    pub fn with_max_exponent_bits(&mut self, bits: u64) -> &mut Self {
+        self.max_exponent_bits = bits;
         self
     }
     pub fn with_modulus(&mut self, p: BigUint, q: BigUint) -> &mut Self {
         self.modulus = Some((p, q));
         self
     }
-    pub fn with_rng(&mut self, rng: Box<dyn RsaCsprng>) -> &mut Self {
-        self.rng = Some(rng);
+    /// Stores `rng` in the builder, boxed. A `Box<dyn RsaCsprng>` can be passed as well.
+    ///
+    /// To generate a key with a borrowed rng instead, see `KeyPairBuilder::create_keypair_with_rng`.
+    pub fn with_rng<R: RsaCsprng + 'static>(&mut self, rng: R) -> &mut Self {
+        self.rng = Some(Box::new(rng));
         self
     }
     pub fn with_iterations(&mut self, iterations: usize) -> &mut Self {
         self.miller_rabin_iterations = iterations;
         self
     }
+    pub fn with_primality_test(&mut self, primality_test: Box<dyn PrimalityTest>) -> &mut Self {
+        self.primality_test = Some(primality_test);
+        self
+    }
+    pub fn with_sieve_size(&mut self, sieve_size: SieveSize) -> &mut Self {
+        self.sieve_size = sieve_size;
+        self
+    }
+    /// Searches for each prime on `threads` worker threads, each with its own CSPRNG stream
+    /// seeded from the builder's `rng`, and keeps the first prime found.
+    ///
+    /// Since the threads race each other, the generated key is not reproducible for
+    /// more than one thread, even with a seeded `rng`. The default is a single thread.
+    #[cfg(feature = "parallel")]
+    pub fn with_threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.max(1);
+        self
+    }
+    pub fn with_key_size(&mut self, key_size: KeySize) -> &mut Self {
+        self.key_size = key_size;
+        self
+    }
+    pub fn with_prime_count(&mut self, count: usize) -> &mut Self {
+        self.prime_count = count;
+        self
+    }
+
+    /// Enables FIPS 186-5 compliant key generation, which enforces that:
+    /// - the modulus is the product of 2 primes, and at least 2048 bits long
+    /// - `2^16 < e < 2^256`, and `e` is odd (the default `e` is the fixed 65537, and a random
+    ///   exponent is only drawn from this range after [`KeyPairBuilder::with_random_exponent`])
+    /// - `p, q >= sqrt(2) * 2^(nlen/2 - 1)`, and `gcd(p - 1, e) = gcd(q - 1, e) = 1`
+    /// - `|p - q| > 2^(nlen/2 - 100)`
+    /// - `d > 2^(nlen/2)`
+    ///
+    /// Primes, including the primes of a specified modulus, are tested with at least the number
+    /// of Miller-Rabin rounds from Table B.1, and a specified primality test only runs in addition
+    /// to those rounds.
+    ///
+    /// The key pair must pass a pairwise consistency test before it's returned.
+    ///
+    /// Generated primes that violate a constraint are discarded and drawn again,
+    /// while a specified modulus that violates a constraint results in an error.
+    pub fn with_fips_mode(&mut self, fips_mode: bool) -> &mut Self {
+        self.fips_mode = fips_mode;
+        self
+    }
 
     /// Consumes fields
-    /// TODO:
-    /// - prime-gen multithreading?
     pub fn create_keypair(&mut self) -> Result<KeyPair, RsaError> {
-        let mut rng = self.rng.take().unwrap_or(Box::new(StdRng::from_entropy()));
-        let mr_iterations = self.miller_rabin_iterations;
+        let mut rng = self
+            .rng
+            .take()
+            .unwrap_or_else(|| Box::new(StdRng::from_entropy()));
 
-        let modulus = self.modulus.take().unwrap_or_else(|| {
-            (
-                generate_candidate_prime(&mut rng, mr_iterations),
-                generate_candidate_prime(&mut rng, mr_iterations),
-            )
-        });
+        self.create_keypair_with_rng(&mut rng)
+    }
+
+    /// Like `create_keypair`, but draws all randomness from the borrowed `rng`,
+    /// e.g. a `&mut ThreadRng` or `&mut OsRng`. Any rng stored with `with_rng` is ignored.
+    ///
+    /// Consumes fields
+    pub fn create_keypair_with_rng<R: RsaCsprng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<KeyPair, RsaError> {
+        if !(2..=RSA_MAX_PRIME_COUNT).contains(&self.prime_count) {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                format!(
+                    "unsupported prime count {}: must be between 2 and {}.",
+                    self.prime_count, RSA_MAX_PRIME_COUNT
+                ),
+            ));
+        }
+        if self.modulus.is_some() && self.prime_count != 2 {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                String::from("a specified modulus can only be used with 2 primes."),
+            ));
+        }
+
+        if self.fips_mode {
+            return self.create_fips_keypair(rng);
+        }
+
+        let exponent = match std::mem::take(&mut self.exponent) {
+            PublicExponent::Fixed(e) => {
+                validate_exponent(&e, self.max_exponent_bits)?;
+                Some(e)
+            }
+            PublicExponent::Random => None,
+        };
+
+        let primality_test = self
+            .primality_test
+            .take()
+            .unwrap_or(Box::new(MillerRabin::new(self.miller_rabin_iterations)));
+        let generation = self.prime_generation(primality_test.as_ref());
+        let modulus = self.modulus.take();
 
-        let lambda = carmichael_totient(&modulus.0, &modulus.1);
+        let (primes, lambda) = loop {
+            let primes = match &modulus {
+                Some((p, q)) => vec![p.clone(), q.clone()],
+                None => generate_primes(rng, &generation, self.key_size.bits(), self.prime_count)?,
+            };
 
-        let exponent = self.exponent.take().unwrap_or_else(|| {
+            let lambda = carmichael_totient_multi_prime(&primes);
+
+            match &exponent {
+                Some(e) if !e.gcd(&lambda).is_one() => {
+                    if modulus.is_some() {
+                        return Err(RsaError::new(
+                            RsaErrorKind::OptionsError,
+                            format!(
+                                "public exponent {} is not coprime to the totient of the primes.",
+                                e
+                            ),
+                        ));
+                    }
+                    // draw new primes rather than failing
+                    continue;
+                }
+                _ => break (primes, lambda),
+            }
+        };
+
+        let exponent = exponent.unwrap_or_else(|| {
             // compute carmichael totient = lambda
             // look for values of e that are coprime to lambda
             let three = BigUint::ZERO + 3u32;
@@ -126,22 +481,272 @@ impl KeyPairBuilder {
             RsaError::new(
                 RsaErrorKind::OptionsError,
                 format!(
-                    "unable to find modular inverse of {} with respect to {}.",
-                    exponent, lambda
+                    "public exponent {} is not coprime to the totient of the primes.",
+                    exponent
                 ),
             )
         })?;
 
-        let n = &modulus.0 * &modulus.1;
+        let n = primes.iter().product::<BigUint>();
 
         let pk = RsaPublicKey::new(exponent.clone(), n.clone());
-        let sk = RsaPrivateKey::with_values(n, exponent, secret, modulus.0, modulus.1)?;
+        let sk = RsaPrivateKey::with_values(n, exponent, secret, primes)?;
 
         Ok(KeyPair {
             public_key: pk,
             private_key: sk,
         })
     }
+
+    /// [FIPS 186-5, Appendix A.1.3](https://csrc.nist.gov/pubs/fips/186-5/final)
+    fn create_fips_keypair<R: RsaCsprng>(&mut self, rng: &mut R) -> Result<KeyPair, RsaError> {
+        if self.prime_count != 2 {
+            return Err(fips_error(String::from(
+                "multi-prime keys are not allowed in FIPS mode.",
+            )));
+        }
+
+        let modulus = self.modulus.take();
+        let nlen = match &modulus {
+            Some((p, q)) => (p * q).bits(),
+            None => self.key_size.bits(),
+        };
+        if nlen < FIPS_MIN_MODULUS_BIT_LENGTH {
+            return Err(fips_error(format!(
+                "modulus must be at least {} bits, got {}.",
+                FIPS_MIN_MODULUS_BIT_LENGTH, nlen
+            )));
+        }
+
+        let e = match std::mem::take(&mut self.exponent) {
+            PublicExponent::Fixed(e) => e,
+            PublicExponent::Random => {
+                // an odd integer in (2^16, 2^256)
+                let mut e = rng.gen_biguint_range(
+                    &((BigUint::one() << 16u32) + 1u32),
+                    &(BigUint::one() << 256u32),
+                );
+                e.set_bit(0, true);
+                e
+            }
+        };
+        if e.is_even() || e <= BigUint::one() << 16u32 || e >= BigUint::one() << 256u32 {
+            return Err(fips_error(format!(
+                "public exponent {} must be odd, and satisfy 2^16 < e < 2^256.",
+                e
+            )));
+        }
+
+        let fips_test = FipsMillerRabin::new(self.miller_rabin_iterations);
+        let primality_test: Box<dyn PrimalityTest> = match self.primality_test.take() {
+            // a specified test can't replace the rounds Table B.1 requires, only add to them
+            Some(test) => Box::new(AllOf(vec![Box::new(fips_test), test])),
+            None => Box::new(fips_test),
+        };
+        let generation = self.prime_generation(primality_test.as_ref());
+
+        let half = nlen / 2;
+        // floor(sqrt(2) * 2^(nlen/2 - 1)) = floor(sqrt(2^(nlen - 1)))
+        let prime_lower_bound = (BigUint::one() << (nlen - 1)).sqrt();
+        let min_prime_distance = BigUint::one() << (half - 100);
+        let min_private_exponent = BigUint::one() << half;
+
+        let is_valid_prime = |r: &BigUint| r > &prime_lower_bound && (r - 1u32).gcd(&e).is_one();
+
+        loop {
+            let (p, q) = match &modulus {
+                Some((p, q)) => (p.clone(), q.clone()),
+                None => {
+                    let mut draw = || loop {
+                        let r = generation.generate(rng, half)?;
+                        if is_valid_prime(&r) {
+                            break Ok::<_, RsaError>(r);
+                        }
+                    };
+
+                    (draw()?, draw()?)
+                }
+            };
+
+            let lambda = carmichael_totient_multi_prime(&[p.clone(), q.clone()]);
+            let d = e.modinv(&lambda);
+            let distance = if p > q { &p - &q } else { &q - &p };
+
+            // generated primes have already passed the test
+            let violation = if modulus.is_some()
+                && !(primality_test.is_prime(rng, &p) && primality_test.is_prime(rng, &q))
+            {
+                Some("p and q must be prime")
+            } else if !is_valid_prime(&p) || !is_valid_prime(&q) {
+                Some("p and q must be at least sqrt(2) * 2^(nlen/2 - 1), with p - 1, q - 1 coprime to e")
+            } else if distance <= min_prime_distance {
+                Some("|p - q| must be greater than 2^(nlen/2 - 100)")
+            } else if d.as_ref().is_none_or(|d| d <= &min_private_exponent) {
+                Some("d must be greater than 2^(nlen/2)")
+            } else {
+                None
+            };
+
+            if let Some(violation) = violation {
+                if modulus.is_some() {
+                    return Err(fips_error(String::from(violation)));
+                }
+                continue;
+            }
+
+            let n = &p * &q;
+            let pk = RsaPublicKey::new(e.clone(), n.clone());
+            let sk = RsaPrivateKey::with_values(n, e, d.unwrap(), vec![p, q])?;
+
+            pairwise_consistency_test(rng, &pk, &sk)?;
+
+            return Ok(KeyPair {
+                public_key: pk,
+                private_key: sk,
+            });
+        }
+    }
+}
+
+/// Checks that a specified public exponent is odd, greater than 1, and at most `max_bits` long.
+fn validate_exponent(e: &BigUint, max_bits: u64) -> Result<(), RsaError> {
+    if e <= &BigUint::one() || e.is_even() {
+        return Err(RsaError::new(
+            RsaErrorKind::OptionsError,
+            format!("public exponent {} must be odd and greater than 1.", e),
+        ));
+    }
+    if e.bits() > max_bits {
+        return Err(RsaError::new(
+            RsaErrorKind::OptionsError,
+            format!(
+                "public exponent {} is {} bits long, but at most {} bits are allowed.",
+                e,
+                e.bits(),
+                max_bits
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// A primality test passed only by the candidates that pass all of the tests.
+struct AllOf(Vec<Box<dyn PrimalityTest>>);
+
+impl PrimalityTest for AllOf {
+    fn is_prime(&self, rng: &mut dyn RsaCsprng, prime_candidate: &BigUint) -> bool {
+        self.0
+            .iter()
+            .all(|test| test.is_prime(rng, prime_candidate))
+    }
+}
+
+/// The settings used to generate each prime.
+struct PrimeGeneration<'a> {
+    primality_test: &'a dyn PrimalityTest,
+    sieve_size: SieveSize,
+    #[cfg(feature = "parallel")]
+    threads: usize,
+}
+
+impl KeyPairBuilder {
+    fn prime_generation<'a>(&self, primality_test: &'a dyn PrimalityTest) -> PrimeGeneration<'a> {
+        PrimeGeneration {
+            primality_test,
+            sieve_size: self.sieve_size,
+            #[cfg(feature = "parallel")]
+            threads: self.threads,
+        }
+    }
+}
+
+impl PrimeGeneration<'_> {
+    /// Generates a prime of exactly `bits` bits.
+    fn generate<R: RsaCsprng>(&self, rng: &mut R, bits: u64) -> Result<BigUint, RsaError> {
+        #[cfg(feature = "parallel")]
+        if self.threads > 1 {
+            return generate_prime_parallel(
+                rng,
+                self.primality_test,
+                bits,
+                self.sieve_size,
+                self.threads,
+            );
+        }
+
+        generate_prime_with_test(rng, self.primality_test, bits, self.sieve_size)
+    }
+}
+
+fn validation_error(message: String) -> RsaError {
+    RsaError::new(RsaErrorKind::ValidationError, message)
+}
+
+fn fips_error(message: String) -> RsaError {
+    RsaError::new(
+        RsaErrorKind::OptionsError,
+        format!("FIPS 186-5: {}", message),
+    )
+}
+
+/// Checks that a freshly generated key pair works, by encrypting a random message
+/// with the public key and decrypting it with the private key.
+///
+/// [See SP 800-56B Rev. 2, Section 6.4.1.1](https://csrc.nist.gov/pubs/sp/800/56/b/r2/final)
+fn pairwise_consistency_test<R: RsaCsprng + ?Sized>(
+    rng: &mut R,
+    pk: &RsaPublicKey,
+    sk: &RsaPrivateKey,
+) -> Result<(), RsaError> {
+    let two = BigUint::ZERO + 2u32;
+    let m = rng.gen_biguint_range(&two, &(&pk.modulus - 1u32));
+
+    let c = pk.crypt(&m)?;
+    if c == m || sk.crypt(&c)? != m {
+        return Err(RsaError::new(
+            RsaErrorKind::CryptographyError,
+            String::from("pairwise consistency test failed"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Generates `count` distinct primes whose product is exactly `bits` bits long.
+///
+/// As in OpenSSL, the bits are split evenly between the primes, with the first primes
+/// taking any remainder. Since each prime has its top two bits set, a two-prime modulus always
+/// has the right length, but the product of more primes may fall one bit short,
+/// in which case all of the primes are drawn again.
+fn generate_primes<R: RsaCsprng>(
+    rng: &mut R,
+    generation: &PrimeGeneration,
+    bits: u64,
+    count: usize,
+) -> Result<Vec<BigUint>, RsaError> {
+    let quotient = bits / count as u64;
+    let remainder = bits % count as u64;
+
+    loop {
+        let mut primes: Vec<BigUint> = Vec::with_capacity(count);
+
+        for i in 0..count {
+            let prime_bits = quotient + u64::from((i as u64) < remainder);
+
+            loop {
+                let prime = generation.generate(rng, prime_bits)?;
+                if !primes.contains(&prime) {
+                    primes.push(prime);
+                    break;
+                }
+            }
+        }
+
+        if primes.iter().product::<BigUint>().bits() == bits {
+            return Ok(primes);
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -150,14 +755,75 @@ pub struct KeyPair {
     pub private_key: RsaPrivateKey,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct RsaPublicKey {
     pub modulus: BigUint,
     pub public_exponent: BigUint,
+    /// Values computed from the modulus the first time the key is used. See `Precomputed`.
+    pub(crate) precomputed: Precomputed,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl Debug for RsaPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RsaPublicKey")
+            .field("modulus", &self.modulus)
+            .field("public_exponent", &self.public_exponent)
+            .finish()
+    }
+}
+
+/// The additional primes of a multi-prime key, and their CRT values.
+///
+/// [See source](https://www.rfc-editor.org/rfc/rfc8017#appendix-A.1.2)
+///
+/// Every field is secret, so `Debug` prints none of them. See `RsaPrivateKey::expose_secret`.
+#[derive(Clone, Eq, PartialEq)]
+pub struct OtherPrimeInfo {
+    /// The prime factor `r_i` of `n`, where `i >= 3`.
+    pub prime: BigUint,
+    /// The CRT exponent `d_i = d mod (r_i - 1)`.
+    pub exponent: BigUint,
+    /// The CRT coefficient `t_i = (r_1 * r_2 * ... * r_(i-1))^(-1) mod r_i`.
+    pub coefficient: BigUint,
+}
+
+impl OtherPrimeInfo {
+    pub fn new(prime: BigUint, exponent: BigUint, coefficient: BigUint) -> Self {
+        Self {
+            prime,
+            exponent,
+            coefficient,
+        }
+    }
+}
+
+impl Debug for OtherPrimeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OtherPrimeInfo").finish_non_exhaustive()
+    }
+}
+
+impl Zeroize for OtherPrimeInfo {
+    fn zeroize(&mut self) {
+        zeroize_biguint(&mut self.prime);
+        zeroize_biguint(&mut self.exponent);
+        zeroize_biguint(&mut self.coefficient);
+    }
+}
+
+impl Drop for OtherPrimeInfo {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for OtherPrimeInfo {}
+
+#[derive(Clone, Eq, PartialEq)]
 /// [See source](https://datatracker.ietf.org/doc/html/rfc3447#appendix-A)
+///
+/// `Debug` and `Display` only show the version, the size of the modulus, and the fingerprint
+/// of the public key, so a stray `{:?}` can't leak the key. See `RsaPrivateKey::expose_secret`.
 pub struct RsaPrivateKey {
     pub version: u8,
     pub modulus: BigUint,
@@ -168,6 +834,93 @@ pub struct RsaPrivateKey {
     pub exponent1: BigUint,
     pub exponent2: BigUint,
     pub coefficient: BigUint,
+    /// The primes `r_3, ..., r_u` of a multi-prime key. Empty for two-prime keys.
+    pub other_prime_infos: Vec<OtherPrimeInfo>,
+    /// Whether private key operations are blinded, and the cached blinding factors.
+    pub(crate) blinding: Blinding,
+    /// What private key operations do when a CRT result fails its check.
+    pub(crate) fault_recovery: FaultRecovery,
+    /// Values computed from the modulus and the primes the first time the key is used.
+    /// See `Precomputed`.
+    pub(crate) precomputed: Precomputed,
+}
+
+/// Wipes the private exponent, the primes and the CRT values. The modulus and
+/// public exponent are public, and are left as they are.
+impl Zeroize for RsaPrivateKey {
+    fn zeroize(&mut self) {
+        zeroize_biguint(&mut self.private_exponent);
+        zeroize_biguint(&mut self.prime1);
+        zeroize_biguint(&mut self.prime2);
+        zeroize_biguint(&mut self.exponent1);
+        zeroize_biguint(&mut self.exponent2);
+        zeroize_biguint(&mut self.coefficient);
+        self.other_prime_infos
+            .iter_mut()
+            .for_each(OtherPrimeInfo::zeroize);
+        self.blinding.clear();
+        self.precomputed.clear();
+    }
+}
+
+/// Private keys are wiped when dropped. Every clone is a separate copy of the
+/// key material, which is wiped when that clone is dropped.
+impl Drop for RsaPrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for RsaPrivateKey {}
+
+impl Debug for RsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RsaPrivateKey")
+            .field("version", &self.version)
+            .field("modulus_bits", &self.modulus.bits())
+            .field("fingerprint", &self.public_key().fingerprint())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Display for RsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-bit RSA private key (version {}, {})",
+            self.modulus.bits(),
+            self.version,
+            self.public_key().fingerprint()
+        )
+    }
+}
+
+/// Formats every field of a private key, including the secret ones.
+/// Returned by `RsaPrivateKey::expose_secret`.
+pub struct ExposedPrivateKey<'a>(&'a RsaPrivateKey);
+
+impl Debug for ExposedPrivateKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = self.0;
+        let other_prime_infos: Vec<[&BigUint; 3]> = key
+            .other_prime_infos
+            .iter()
+            .map(|info| [&info.prime, &info.exponent, &info.coefficient])
+            .collect();
+
+        f.debug_struct("RsaPrivateKey")
+            .field("version", &key.version)
+            .field("modulus", &key.modulus)
+            .field("public_exponent", &key.public_exponent)
+            .field("private_exponent", &key.private_exponent)
+            .field("prime1", &key.prime1)
+            .field("prime2", &key.prime2)
+            .field("exponent1", &key.exponent1)
+            .field("exponent2", &key.exponent2)
+            .field("coefficient", &key.coefficient)
+            .field("other_prime_infos", &other_prime_infos)
+            .finish()
+    }
 }
 
 impl RsaPublicKey {
@@ -175,11 +928,56 @@ impl RsaPublicKey {
         Self {
             public_exponent: e,
             modulus: n,
+            precomputed: Precomputed::default(),
         }
     }
+
+    /// The length in octets of the RSA modulus (`k` in RFC8017).
+    pub fn modulus_len(&self) -> usize {
+        (self.modulus.bits() as usize).div_ceil(8)
+    }
+
+    /// The SHA-256 hash of the DER-encoded `RSAPublicKey`, formatted as `SHA256:<hex>`.
+    pub fn fingerprint(&self) -> String {
+        let hash = Sha256::digest(rsa_public_key_der_serialize(self.clone()));
+        let hex: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+
+        format!("SHA256:{}", hex)
+    }
+
+    /// Checks that the key is usable, as described in
+    /// [RFC8017, Section 3.1](https://www.rfc-editor.org/rfc/rfc8017#section-3.1):
+    /// - the modulus `n` is odd, and at least `RSA_MIN_MODULUS_BIT_LENGTH` bits long
+    /// - the public exponent `e` is odd, and `3 <= e < n`
+    ///
+    /// Returns a `ValidationError` naming the first violated condition.
+    pub fn validate(&self) -> Result<(), RsaError> {
+        let n = &self.modulus;
+        let e = &self.public_exponent;
+
+        if n.bits() < RSA_MIN_MODULUS_BIT_LENGTH {
+            return Err(validation_error(format!(
+                "modulus is {} bits long, but at least {} bits are required.",
+                n.bits(),
+                RSA_MIN_MODULUS_BIT_LENGTH
+            )));
+        }
+        if n.is_even() {
+            return Err(validation_error(String::from("modulus is even.")));
+        }
+        if e.is_even() || *e < BigUint::from(3u32) || e >= n {
+            return Err(validation_error(format!(
+                "public exponent {} must be odd, and satisfy 3 <= e < n.",
+                e
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 impl RsaPrivateKey {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         version: u8,
         modulus: BigUint,
@@ -201,16 +999,354 @@ impl RsaPrivateKey {
             exponent1,
             exponent2,
             coefficient,
+            other_prime_infos: Vec::new(),
+            blinding: Blinding::default(),
+            fault_recovery: FaultRecovery::default(),
+            precomputed: Precomputed::default(),
         }
     }
 
+    /// Adds the additional primes of a multi-prime key.
+    pub fn with_other_prime_infos(mut self, other_prime_infos: Vec<OtherPrimeInfo>) -> Self {
+        self.other_prime_infos = other_prime_infos;
+        self
+    }
+
+    /// Turns blinding of private key operations on or off. Blinding is on by default,
+    /// and should only be turned off where timing side channels don't matter.
+    /// See `RsaPrimitive::crypt`.
+    pub fn with_blinding(mut self, enabled: bool) -> Self {
+        self.blinding.enabled = enabled;
+        self
+    }
+
+    /// Draws the blinding factors from `rng`, rather than from a `StdRng`
+    /// seeded from the operating system.
+    pub fn with_blinding_rng<R: RsaCsprng + Send + 'static>(mut self, rng: R) -> Self {
+        self.blinding.set_rng(Box::new(rng));
+        self
+    }
+
+    /// Whether private key operations are blinded.
+    pub fn is_blinded(&self) -> bool {
+        self.blinding.enabled
+    }
+
+    /// Chooses what private key operations do when a CRT result fails its check.
+    /// By default, they fail. See `FaultRecovery`.
+    pub fn with_fault_recovery(mut self, fault_recovery: FaultRecovery) -> Self {
+        self.fault_recovery = fault_recovery;
+        self
+    }
+
+    /// The public half of the key.
+    pub fn public_key(&self) -> RsaPublicKey {
+        RsaPublicKey::new(self.public_exponent.clone(), self.modulus.clone())
+    }
+
+    /// Gives a value whose `Debug` output includes the secret integers, which the
+    /// `Debug` output of the key itself leaves out. Only use it where the raw values are
+    /// really needed, since anything it's formatted into contains the key.
+    pub fn expose_secret(&self) -> ExposedPrivateKey<'_> {
+        ExposedPrivateKey(self)
+    }
+
+    /// The number of prime factors of the RSA modulus.
+    pub fn prime_count(&self) -> usize {
+        2 + self.other_prime_infos.len()
+    }
+
+    /// The length in octets of the RSA modulus (`k` in RFC8017).
+    pub fn modulus_len(&self) -> usize {
+        (self.modulus.bits() as usize).div_ceil(8)
+    }
+
+    /// Builds a two-prime key from its primes `p, q` and public exponent `e`, computing `d`
+    /// as described by `mode`, and the CRT values. `p` becomes `prime1` and `q` becomes `prime2`.
+    ///
+    /// Fails with an `OptionsError` unless `p` and `q` are distinct primes of the same bit length,
+    /// and `e` is invertible. Fails with a `ValidationError` if the public key `(n, e)`
+    /// doesn't pass `RsaPublicKey::validate`.
+    pub fn from_primes(
+        p: BigUint,
+        q: BigUint,
+        e: BigUint,
+        mode: DerivationMode,
+    ) -> Result<Self, RsaError> {
+        if p == q {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                String::from("the primes must be distinct."),
+            ));
+        }
+        if p.bits() != q.bits() {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                format!(
+                    "the primes must have the same bit length, got {} and {} bits.",
+                    p.bits(),
+                    q.bits()
+                ),
+            ));
+        }
+        for (name, r) in [("p", &p), ("q", &q)] {
+            if !baillie_psw_is_prime(r) {
+                return Err(RsaError::new(
+                    RsaErrorKind::OptionsError,
+                    format!("{} is not prime.", name),
+                ));
+            }
+        }
+
+        let n = &p * &q;
+        let pk = RsaPublicKey::new(e, n);
+        pk.validate()?;
+        let RsaPublicKey {
+            modulus: n,
+            public_exponent: e,
+            ..
+        } = pk;
+
+        let p1 = &p - 1u32;
+        let q1 = &q - 1u32;
+        let totient = match mode {
+            DerivationMode::Lambda => p1.lcm(&q1),
+            DerivationMode::Phi => p1 * q1,
+        };
+
+        let d = e.modinv(&totient).ok_or_else(|| {
+            RsaError::new(
+                RsaErrorKind::OptionsError,
+                format!(
+                    "public exponent {} is not coprime to the totient of p and q.",
+                    e
+                ),
+            )
+        })?;
+
+        Self::with_values(n, e, d, vec![p, q])
+    }
+
+    /// Reconstructs a two-prime key, including its CRT values, from only the modulus `n`,
+    /// the public exponent `e`, and the private exponent `d`.
+    ///
+    /// The modulus is factored with the randomized algorithm of
+    /// [SP 800-56B Rev. 2, Appendix C.2](https://csrc.nist.gov/pubs/sp/800/56/b/r2/final),
+    /// which also appears in [RFC8017, Section 3.2](https://www.rfc-editor.org/rfc/rfc8017#section-3.2) note 2.
+    /// `prime1` is the larger of the two primes, and `rng` draws the bases the algorithm tries.
+    /// Fails if `n` isn't the product of two distinct primes for which `e` and `d` are inverses.
+    pub fn from_components<R: RsaCsprng + ?Sized>(
+        rng: &mut R,
+        n: BigUint,
+        e: BigUint,
+        d: BigUint,
+    ) -> Result<Self, RsaError> {
+        let two = BigUint::ZERO + 2u32;
+        if n.is_even() || n <= two || e <= BigUint::one() || e >= n || d.is_zero() || d >= n {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                String::from("components must satisfy 1 < e < n, 0 < d < n, and n must be odd."),
+            ));
+        }
+
+        // k = e * d - 1 is a multiple of lambda(n), so k = 2^t * r with r odd
+        let k = &e * &d - 1u32;
+        let t = k.trailing_zeros().unwrap_or(0);
+        let r = &k >> t;
+        let n_minus_one = &n - 1u32;
+
+        let mut factor = None;
+        'attempts: for _ in 0..FACTORING_ATTEMPTS {
+            let g = rng.gen_biguint_range(&two, &n_minus_one);
+            let mut y = g.modpow(&r, &n);
+            if y.is_one() || y == n_minus_one {
+                continue;
+            }
+
+            // look for a nontrivial square root of 1 mod n among g^r, g^2r, ..., g^(2^(t-1) r)
+            for _ in 0..t {
+                let x = y.modpow(&two, &n);
+                if x.is_one() {
+                    factor = Some((&y - 1u32).gcd(&n));
+                    break 'attempts;
+                }
+                if x == n_minus_one {
+                    continue 'attempts;
+                }
+                y = x;
+            }
+
+            // g^k != 1 mod n, so k isn't a multiple of lambda(n)
+            break;
+        }
+
+        let (p, q) = match factor {
+            Some(p) if !p.is_one() && p != n => {
+                let q = &n / &p;
+                (p.clone().max(q.clone()), p.min(q))
+            }
+            _ => {
+                return Err(RsaError::new(
+                    RsaErrorKind::OptionsError,
+                    String::from("unable to factor the modulus with the given exponents."),
+                ))
+            }
+        };
+
+        if !baillie_psw_is_prime(&p) || !baillie_psw_is_prime(&q) {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                String::from("the modulus is not the product of two primes."),
+            ));
+        }
+
+        // a square root of 1 can turn up even if k is only a multiple of lambda(n) / 2
+        if !(&e * &d % carmichael_totient(&p, &q)).is_one() {
+            return Err(RsaError::new(
+                RsaErrorKind::OptionsError,
+                String::from("e and d are not inverses modulo lambda(n)."),
+            ));
+        }
+
+        Self::with_values(n, e, d, vec![p, q])
+    }
+
+    /// Checks that the key is consistent, as described in
+    /// [RFC8017, Section 3.2](https://www.rfc-editor.org/rfc/rfc8017#section-3.2):
+    /// - the public key `(n, e)` passes `RsaPublicKey::validate`
+    /// - the version is 1 exactly if the key has more than 2 primes
+    /// - every prime `r_i` is prime, and they are distinct
+    /// - `n` is the product of the primes
+    /// - `0 < d < n`, and `e * d = 1 mod lambda(n)`
+    /// - `e * dP = 1 mod (p - 1)`, `e * dQ = 1 mod (q - 1)` and `e * d_i = 1 mod (r_i - 1)`
+    /// - `q * qInv = 1 mod p` and `(r_1 * ... * r_(i-1)) * t_i = 1 mod r_i`
+    /// - a random message survives encryption with the public key and decryption with the private key
+    ///
+    /// Primes are tested with Baillie-PSW. Returns a `ValidationError` naming the first violated condition.
+    pub fn validate(&self) -> Result<(), RsaError> {
+        let n = &self.modulus;
+        let e = &self.public_exponent;
+        let d = &self.private_exponent;
+
+        let pk = self.public_key();
+        pk.validate()?;
+
+        let expected_version = if self.other_prime_infos.is_empty() {
+            RSA_VERSION
+        } else {
+            RSA_MULTI_PRIME_VERSION
+        };
+        if self.version != expected_version {
+            return Err(validation_error(format!(
+                "version {} doesn't match a key with {} primes.",
+                self.version,
+                self.prime_count()
+            )));
+        }
+
+        let primes: Vec<&BigUint> = [&self.prime1, &self.prime2]
+            .into_iter()
+            .chain(self.other_prime_infos.iter().map(|info| &info.prime))
+            .collect();
+
+        for (i, r) in primes.iter().enumerate() {
+            if !baillie_psw_is_prime(r) {
+                return Err(validation_error(format!("prime r_{} is not prime.", i + 1)));
+            }
+            if primes[..i].contains(r) {
+                return Err(validation_error(format!("prime r_{} is repeated.", i + 1)));
+            }
+        }
+
+        if primes.iter().copied().product::<BigUint>() != *n {
+            return Err(validation_error(String::from(
+                "modulus is not the product of the primes.",
+            )));
+        }
+
+        let primes: Vec<BigUint> = primes.into_iter().cloned().collect();
+        let lambda = carmichael_totient_multi_prime(&primes);
+        if d.is_zero() || d >= n {
+            return Err(validation_error(String::from(
+                "private exponent must satisfy 0 < d < n.",
+            )));
+        }
+        if !(e * d % &lambda).is_one() {
+            return Err(validation_error(String::from(
+                "private exponent doesn't satisfy e * d = 1 mod lambda(n).",
+            )));
+        }
+
+        let is_crt_exponent = |d_i: &BigUint, r: &BigUint| {
+            let r1 = r - 1u32;
+            *d_i < r1 && (e * d_i % r1).is_one()
+        };
+        let is_crt_coefficient =
+            |t_i: &BigUint, product: &BigUint, r: &BigUint| t_i < r && (product * t_i % r).is_one();
+
+        if !is_crt_exponent(&self.exponent1, &self.prime1) {
+            return Err(validation_error(String::from(
+                "exponent1 doesn't satisfy e * dP = 1 mod (p - 1).",
+            )));
+        }
+        if !is_crt_exponent(&self.exponent2, &self.prime2) {
+            return Err(validation_error(String::from(
+                "exponent2 doesn't satisfy e * dQ = 1 mod (q - 1).",
+            )));
+        }
+        if !is_crt_coefficient(&self.coefficient, &self.prime2, &self.prime1) {
+            return Err(validation_error(String::from(
+                "coefficient doesn't satisfy q * qInv = 1 mod p.",
+            )));
+        }
+
+        let mut product = &self.prime1 * &self.prime2;
+        for (i, info) in self.other_prime_infos.iter().enumerate() {
+            if !is_crt_exponent(&info.exponent, &info.prime) {
+                return Err(validation_error(format!(
+                    "exponent of r_{} doesn't satisfy e * d_i = 1 mod (r_i - 1).",
+                    i + 3
+                )));
+            }
+            if !is_crt_coefficient(&info.coefficient, &product, &info.prime) {
+                return Err(validation_error(format!(
+                    "coefficient of r_{} doesn't satisfy (r_1 * ... * r_(i-1)) * t_i = 1 mod r_i.",
+                    i + 3
+                )));
+            }
+            product *= &info.prime;
+        }
+
+        let two = BigUint::ZERO + 2u32;
+        let m = StdRng::from_entropy().gen_biguint_range(&two, &(n - 1u32));
+        if self.crypt(&pk.crypt(&m)?)? != m {
+            return Err(validation_error(String::from(
+                "pairwise consistency test failed.",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Computes the CRT values of a key from its primes `r_1, ..., r_u`,
+    /// where `r_1 = p` and `r_2 = q`.
     fn with_values(
         n: BigUint,
         e: BigUint,
         d: BigUint,
-        p: BigUint,
-        q: BigUint,
+        primes: Vec<BigUint>,
     ) -> Result<Self, RsaError> {
+        let mut primes = primes.into_iter();
+        let (p, q) = match (primes.next(), primes.next()) {
+            (Some(p), Some(q)) => (p, q),
+            _ => {
+                return Err(RsaError::new(
+                    RsaErrorKind::OptionsError,
+                    String::from("an RSA modulus needs at least 2 primes."),
+                ))
+            }
+        };
+
         let p1 = &p - 1u32;
         let q1 = &q - 1u32;
 
@@ -220,15 +1356,36 @@ impl RsaPrivateKey {
         let qinv = q.modinv(&p).ok_or_else(|| {
             RsaError::new(
                 RsaErrorKind::OptionsError,
-                format!(
-                    "Unable to compute modular inverse of {} with respect to {}.",
-                    q, p
-                ),
+                String::from("q is not invertible modulo p."),
             )
         })?;
 
+        let mut other_prime_infos = Vec::new();
+        let mut product = &p * &q;
+        for (i, r) in primes.enumerate() {
+            let d_i = &d % (&r - 1u32);
+            let t_i = product.modinv(&r).ok_or_else(|| {
+                RsaError::new(
+                    RsaErrorKind::OptionsError,
+                    format!(
+                        "the product of the preceding primes is not invertible modulo r_{}.",
+                        i + 3
+                    ),
+                )
+            })?;
+
+            product *= &r;
+            other_prime_infos.push(OtherPrimeInfo::new(r, d_i, t_i));
+        }
+
+        let version = if other_prime_infos.is_empty() {
+            RSA_VERSION
+        } else {
+            RSA_MULTI_PRIME_VERSION
+        };
+
         Ok(Self {
-            version: RSA_VERSION,
+            version,
             modulus: n,
             public_exponent: e,
             private_exponent: d,
@@ -237,6 +1394,10 @@ impl RsaPrivateKey {
             exponent1: dp,
             exponent2: dq,
             coefficient: qinv,
+            other_prime_infos,
+            blinding: Blinding::default(),
+            fault_recovery: FaultRecovery::default(),
+            precomputed: Precomputed::default(),
         })
     }
 }
@@ -1,8 +1,10 @@
 pub mod crypto;
 pub mod errors;
 pub mod keygen;
-mod mask;
+pub mod mask;
+mod montgomery;
 pub mod serial;
+pub mod signature;
 mod static_init;
 pub mod util;
 
@@ -11,14 +13,41 @@ mod test {
     use crate::crypto::*;
     use crate::keygen::*;
     use crate::serial::*;
-    use num::{BigUint, One};
+    use crate::util::*;
+    use num::{BigUint, Integer, One};
     use num_bigint::RandBigInt;
     use rand::rngs::StdRng;
     use rand::SeedableRng;
 
-    use std::sync::OnceLock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, OnceLock};
 
     static KP: OnceLock<KeyPair> = OnceLock::new();
+    static MULTI_PRIME_KP: OnceLock<KeyPair> = OnceLock::new();
+
+    /// 1024-bit, 3-prime key generated by OpenSSL 3.0
+    const OPENSSL_MULTI_PRIME_KEY_DER: &str =
+        "3082027e02010102818100dbdb349f44868d608ab2e52073c7f44184f1347e3f\
+        a2582701ed86c73d0be51013b561ae78ac9fddf0f30a3182d64a24352f8065f8\
+        02118f5bb907ce0c5bb57e9a0d31af831e7782b39c823de5e1e29c3ff771b7cf\
+        2f235e6fcfff80725522b70b79fb87f066297e54a43382d9767fe1996aaa97c2\
+        33f681adf54a05fbaa8c83020301000102818100927d3d53bda1f58355071bda\
+        02ab4159828e00cef9f15ed126dcb00802e9f107444a7fba739779b1b4c0ff84\
+        e7c8becad3d0acc46dc57c599b8b658d8ab2b08a412353b64bcc821b3b5ca57e\
+        d25fea458fc4d035f3f50d2df804c40c9b7fe645e4d8643a8fa048728d6a963e\
+        a9b59c76bdbba7d7f73fb486f0c50c3bd23543d9022b3ce73d75035f22358af8\
+        c814b2881ef3c9ae1c32c0c8d17afa827f7e027fbc092a73895ce4c74373a5ed\
+        df022b1fe342ee21ad6934297f3a22e227f4e6e048d30b6f786de8b450d6b88e\
+        d44f581316b9e0c7c682d7329f1b022b195563061281f71be893f5be4e493afe\
+        d38cbe240a773e53f6726410098012ba895307f7811eb61b1c1167022b06c0e3\
+        13fc1df6020e27dbf03765af0096cfd5a2be610b2615ca692f7d5089dd656d92\
+        c0808f1276f149b1022b0b831f5103c24f8aedb8c464bfc05a3982322acd1643\
+        23509929c4f1f735c82431e0b17c5523be894e2d1830818a308187022b1cfb27\
+        4aafdb045d6307b49ae56a3f78c5b7575442a8c025ac9dc0a94454672936eb20\
+        65c9b1c227b31e67022b022ab5115c7c9fa2ebd893a4512ad47c0f2c5a172eb4\
+        b74919d2e1a8c637194f778fb4e86760270818d859022b01d4b1cf75c64f07ca\
+        38eb8d08b93cc42778156cfe2fec1aceed8dbae096ee2bc5d836f260c86f8e17\
+        e7de";
 
     fn default_keypair() -> &'static KeyPair {
         KP.get_or_init(|| {
@@ -29,6 +58,17 @@ mod test {
         })
     }
 
+    fn multi_prime_keypair() -> &'static KeyPair {
+        MULTI_PRIME_KP.get_or_init(|| {
+            KeyPairBuilder::default()
+                .with_rng(StdRng::seed_from_u64(17))
+                .with_key_size(KeySize::Bits1024)
+                .with_prime_count(3)
+                .create_keypair()
+                .unwrap()
+        })
+    }
+
     #[test]
     fn generate_rsa_keypair_seeded() {
         const SEED: u64 = 100;
@@ -41,7 +81,6 @@ mod test {
             .create_keypair()
             .expect("fatal error: couldn't generate keypair");
 
-
         let pk = kp.public_key;
         let sk = kp.private_key;
 
@@ -57,6 +96,486 @@ mod test {
         assert_eq!((&pk.public_exponent * &sk.exponent2) % &q1, one);
     }
 
+    #[test]
+    fn generate_rsa_keypair_borrowed_rng() {
+        let mut builder = KeyPairBuilder::default();
+        builder.with_key_size(KeySize::Bits1024);
+
+        let mut rng = StdRng::seed_from_u64(16);
+        let kp = builder.create_keypair_with_rng(&mut rng).unwrap();
+        assert_eq!(kp.public_key.modulus.bits(), 1024);
+
+        // the same seed gives the same key, whether the rng is borrowed or boxed
+        let boxed = builder
+            .with_rng(StdRng::seed_from_u64(16))
+            .create_keypair()
+            .unwrap();
+        assert_eq!(boxed.private_key, kp.private_key);
+
+        let kp = builder
+            .with_prime_count(3)
+            .create_keypair_with_rng(&mut rand::thread_rng())
+            .unwrap();
+        assert_eq!(kp.private_key.prime_count(), 3);
+    }
+
+    #[test]
+    fn generate_rsa_keypair_from_seed() {
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+        // known answers for version 1, as RSAPrivateKey DER; these must never change
+        let vectors = [
+            (
+                2,
+                "3082025c02010002818100ef0651cdf708106a37a293a668c66d1bfa673d602b\
+                 6354c0e2e0ac1e3449f469ac2353e5707ff6a4bab53900d93023b4184764ded6\
+                 08eb55ee682e0555ead70c6cc1bd390ec56efcca88d88fe9960c6fc67841903b\
+                 e3d63174a5b537bdd084259141f06b8395bc4f1240cc0e68f92d4d2679ce70a1\
+                 01b0862ef1d622a59dc6c902030100010281800348c671fa8d795044506f0fef\
+                 cb9e2f2ebb216c435c5b8b44f2ca5aa5e4483ff5760cce8fc3aa5b6c17565fad\
+                 1c4cb94a461cdc34fbad642260fb14e6afd1e19a862654c5e361a42725694c25\
+                 96d49ee87eb66edc67e432942ea8c466baee145eebbd56b79e0d19e288b179e8\
+                 b1c389040e37894198cb60ed2856d7a40fb253024100fda024fca9e90d40ede5\
+                 0fd29893aed64172165ea53e137c98902352571472e23b2637f1522851f3601a\
+                 43a05944237ebae5041a0e185cc8fc38d4d02dc12e83024100f1432e80d83cbf\
+                 1cb8cd29f2efca28cbf2950b0c648a94f1df91f5a9d8121e41343f2ec0b566f4\
+                 f74dead4728fbaa8a49ab4e3815238cacad566b449419bf3c3024100f9023a4e\
+                 3723b0f78699d6a7155629da01f7592f235d871455556ed870d68ad4c2ee0b0e\
+                 cb72fac2d01ff9357d6f00ca740455ba9f0c1c4cd815941bb05072b102403b8f\
+                 5d449b9d6b84b8ae95838ee4f66b5ae8a68099b5888da9a6cbe1f1208fed82eb\
+                 722c4adc2eef7a81d58eaea2e30d37d01e3607c9536efd7dcc5625674da70240\
+                 4e98de43f41c1ab8be08dc1a214afb1ea791848f88ba66ee81b5634919e913a7\
+                 3664db09eae64b09629c96c1cd1c76c9a00bffe337fefb26a55fd30f0c703c12",
+            ),
+            (
+                3,
+                "3082027d02010102818100a082b814e662df3ba8fdea9e808705d5ff30f2390c\
+                 7c8284f1b6fea0637d8de80f7a932820494643bb84f1f54bfabe57eca8b99daa\
+                 634b5971be8556c6f10d2449fdcf52f8695fad21abf5d7ff7c50f3ab92cb7f49\
+                 012d43841a3f3fe326ce7d7bad6052cc8d6cf0c56f7514414bc241a0021b820a\
+                 ec21ac1d0a3909e5e200890203010001028180049d839e66ba3db3df05e793d5\
+                 8b645ea1110acafb05dec124bbe3be10044b82fa647674253b0426250f82f353\
+                 01847a28d72edc359c52c52bda7e8116157f546a62898dcc68dd5fb6bfd722e1\
+                 48fce08ae65b04f67e58bc51ce227c921e2f3c82032b06de9d98d325085d4d40\
+                 af35e82f9d1ac50caf11b5abd2936dee3d46c1022b371353a26501c4b65ec85f\
+                 3fa1ca77da29a0ad703f91ad5fa431bc81ef6f7316920c685ce0dfd4bc0f4a4d\
+                 022b1ab2b33a7a263952808b32977a6841a94d5005c915d65c4ba9b2ced29552\
+                 0fffdd1f45e5451634c1518b41022b1830abee4af110e68d10f30ceed20ffc49\
+                 86624748148312dbfa6072ada8c4e2e7f8fd29b63779a7b40b8d022b140ad92f\
+                 ea292dd938b4c6cb1ed28b907c80b98726c4a0cf105b3296b610400095d07759\
+                 518f0515a790c1022b369f73d351df44015402956cbacb47f2dbd4f3bd57fa06\
+                 6aa652f770a9cc6d501e8c0c5de81ed00be5676130818a308187022b1bf1f2c6\
+                 9cb64ad72dd42063c1ddd5754286a86a5df548d1ff7a97e228dd48df57bd2ab3\
+                 3e62616071caed022b019303c64495d06986df6863b64c34f642252bdfb2235b\
+                 52d93da2c8cedc887a6c8aef98e16ed165898391022b18c0957264a64d89c469\
+                 05369d531f59871067efe9049585650bebcf15fa6f0ca8e6aa62ff3ba4722e02\
+                 5f",
+            ),
+        ];
+
+        for (prime_count, der) in vectors {
+            let params = SeedParams {
+                version: SeedVersion::V1,
+                key_size: KeySize::Bits1024,
+                prime_count,
+                exponent: 65537,
+            };
+
+            let kp = KeyPairBuilder::from_seed(seed, &params)
+                .create_keypair()
+                .unwrap();
+            assert_eq!(
+                *rsa_private_key_der_serialize(kp.private_key.clone()),
+                from_hex(der)
+            );
+            assert_eq!(kp.private_key.prime_count(), prime_count);
+
+            let again = KeyPairBuilder::from_seed(seed, &params)
+                .create_keypair()
+                .unwrap();
+            assert_eq!(again.private_key, kp.private_key);
+        }
+
+        // a different seed or different parameters derive an unrelated key
+        let params = SeedParams {
+            key_size: KeySize::Bits1024,
+            ..Default::default()
+        };
+        let kp = KeyPairBuilder::from_seed(seed, &params)
+            .create_keypair()
+            .unwrap();
+
+        let mut other_seed = seed;
+        other_seed[31] ^= 1;
+        let other = KeyPairBuilder::from_seed(other_seed, &params)
+            .create_keypair()
+            .unwrap();
+        assert_ne!(other.public_key.modulus, kp.public_key.modulus);
+
+        let params = SeedParams {
+            exponent: 3,
+            ..params
+        };
+        let other = KeyPairBuilder::from_seed(seed, &params)
+            .create_keypair()
+            .unwrap();
+        assert_ne!(other.public_key.modulus, kp.public_key.modulus);
+    }
+
+    #[test]
+    fn generate_rsa_keypair_key_size() {
+        let mut builder = KeyPairBuilder::default();
+        builder
+            .with_rng(Box::new(StdRng::seed_from_u64(7)))
+            .with_key_size(KeySize::Bits1024);
+
+        for _ in 0..4 {
+            let kp = builder.create_keypair().unwrap();
+            assert_eq!(kp.public_key.modulus.bits(), 1024);
+            assert_eq!(kp.private_key.prime1.bits(), 512);
+            assert_eq!(kp.private_key.prime2.bits(), 512);
+            assert_eq!(kp.public_key.modulus_len(), 128);
+
+            let m = BigUint::ZERO + 42u32;
+            let c = kp.public_key.crypt(&m).unwrap();
+            assert_eq!(kp.private_key.crypt(&c).unwrap(), m);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn generate_rsa_keypair_parallel() {
+        let mut builder = KeyPairBuilder::default();
+        builder
+            .with_rng(Box::new(StdRng::seed_from_u64(14)))
+            .with_key_size(KeySize::Bits1024)
+            .with_threads(4);
+
+        let kp = builder.create_keypair().unwrap();
+        assert_eq!(kp.public_key.modulus.bits(), 1024);
+
+        let kp = builder.with_prime_count(3).create_keypair().unwrap();
+        assert_eq!(kp.public_key.modulus.bits(), 1024);
+        assert_eq!(kp.private_key.prime_count(), 3);
+
+        let m = BigUint::ZERO + 42u32;
+        let c = kp.public_key.crypt(&m).unwrap();
+        assert_eq!(kp.private_key.crypt(&c).unwrap(), m);
+    }
+
+    #[test]
+    fn generate_rsa_keypair_multi_prime() {
+        let mut builder = KeyPairBuilder::default();
+        builder
+            .with_rng(Box::new(StdRng::seed_from_u64(8)))
+            .with_key_size(KeySize::Bits1024);
+
+        for count in 3..=RSA_MAX_PRIME_COUNT {
+            let kp = builder.with_prime_count(count).create_keypair().unwrap();
+            let sk = kp.private_key;
+
+            assert_eq!(sk.version, RSA_MULTI_PRIME_VERSION);
+            assert_eq!(sk.prime_count(), count);
+            assert_eq!(sk.modulus.bits(), 1024);
+
+            let product = sk
+                .other_prime_infos
+                .iter()
+                .fold(&sk.prime1 * &sk.prime2, |acc, info| acc * &info.prime);
+            assert_eq!(product, sk.modulus);
+
+            // the CRT result matches the plain RSADP result m = c^d mod n
+            let c = BigUint::ZERO + 12345u32;
+            let m = sk.crypt(&c).unwrap();
+            assert_eq!(m, c.modpow(&sk.private_exponent, &sk.modulus));
+            assert_eq!(kp.public_key.crypt(&m).unwrap(), c);
+
+            let der = rsa_private_key_der_serialize(sk.clone());
+            assert_eq!(rsa_private_key_der_deserialize(der).unwrap(), sk);
+        }
+    }
+
+    #[test]
+    fn generate_rsa_keypair_invalid_prime_count() {
+        for count in [0, 1, RSA_MAX_PRIME_COUNT + 1] {
+            assert!(KeyPairBuilder::default()
+                .with_prime_count(count)
+                .create_keypair()
+                .is_err());
+        }
+
+        let p = BigUint::ZERO + 61u32;
+        let q = BigUint::ZERO + 53u32;
+        assert!(KeyPairBuilder::default()
+            .with_modulus(p, q)
+            .with_prime_count(3)
+            .create_keypair()
+            .is_err());
+    }
+
+    #[test]
+    fn test_multi_prime_openssl_key() {
+        let der = from_hex(OPENSSL_MULTI_PRIME_KEY_DER);
+        // "attack at dawn", encrypted by OpenSSL with RSAES-PKCS1-v1_5
+        let ciphertext = from_hex(
+            "bab934f36dd568eb12e14510dc2dd598ffbd253da61feb63e873476a15306c75\
+             75c2485c7feb64baaae66874419bb86625dae1a26f8f941216dab71bb9a79f09\
+             98f078b44026de8088aa4ca5016816c01c68ef43d25bab12369bf1cb708613f5\
+             45a2e1607258cb9fa2a1c3536f86bcc50995ef72402208c0cc14410cfffce986",
+        );
+
+        let sk = rsa_private_key_der_deserialize(der.clone()).unwrap();
+        assert_eq!(sk.version, 1);
+        assert_eq!(sk.prime_count(), 3);
+        assert_eq!(*rsa_private_key_der_serialize(sk.clone()), der);
+
+        assert_eq!(sk.decrypt_pkcs1v15(&ciphertext).unwrap(), b"attack at dawn");
+    }
+
+    #[test]
+    fn test_private_key_redacted_debug() {
+        let kp = multi_prime_keypair();
+        let sk = &kp.private_key;
+
+        let fingerprint = kp.public_key.fingerprint();
+        assert_eq!(sk.public_key().fingerprint(), fingerprint);
+
+        let secrets = [
+            &sk.private_exponent,
+            &sk.prime1,
+            &sk.prime2,
+            &sk.exponent1,
+            &sk.exponent2,
+            &sk.coefficient,
+            &sk.other_prime_infos[0].prime,
+            &sk.other_prime_infos[0].exponent,
+            &sk.other_prime_infos[0].coefficient,
+        ];
+
+        for redacted in [
+            format!("{:?}", sk),
+            format!("{:#?}", sk),
+            format!("{}", sk),
+            format!("{:?}", kp),
+            format!("{:?}", sk.other_prime_infos),
+        ] {
+            for secret in secrets {
+                assert!(!redacted.contains(&secret.to_string()), "{}", redacted);
+                assert!(!redacted.contains(&secret.to_str_radix(16)), "{}", redacted);
+            }
+        }
+
+        let debug = format!("{:?}", sk);
+        assert!(debug.contains("version: 1"));
+        assert!(debug.contains("modulus_bits: 1024"));
+        assert!(debug.contains(&fingerprint));
+        assert!(format!("{}", sk).starts_with("1024-bit RSA private key"));
+
+        let exposed = format!("{:?}", sk.expose_secret());
+        for secret in secrets {
+            assert!(exposed.contains(&secret.to_string()));
+        }
+
+        // SHA-256 of the RSAPublicKey DER, as computed by
+        // `openssl rsa -RSAPublicKey_out -outform DER | sha256sum` for the OpenSSL key
+        let sk = rsa_private_key_der_deserialize(from_hex(OPENSSL_MULTI_PRIME_KEY_DER)).unwrap();
+        assert_eq!(
+            sk.public_key().fingerprint(),
+            "SHA256:c1caa2b669e7f769058ddeb525c0a7ba519e6f08799ad43f0636db362dc89b45"
+        );
+    }
+
+    #[test]
+    fn generate_rsa_keypair_primality_test() {
+        // counts the candidates it is asked about, and defers to Baillie-PSW
+        struct CountingTest(Arc<AtomicUsize>);
+
+        impl PrimalityTest for CountingTest {
+            fn is_prime(&self, _rng: &mut dyn RsaCsprng, prime_candidate: &BigUint) -> bool {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                baillie_psw_is_prime(prime_candidate)
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let kp = KeyPairBuilder::default()
+            .with_rng(Box::new(StdRng::seed_from_u64(11)))
+            .with_key_size(KeySize::Bits1024)
+            .with_primality_test(Box::new(CountingTest(count.clone())))
+            .create_keypair()
+            .unwrap();
+
+        assert!(count.load(Ordering::Relaxed) >= 2);
+        assert!(baillie_psw_is_prime(&kp.private_key.prime1));
+        assert!(baillie_psw_is_prime(&kp.private_key.prime2));
+
+        let kp = KeyPairBuilder::default()
+            .with_key_size(KeySize::Bits1024)
+            .with_primality_test(Box::new(BailliePsw))
+            .create_keypair()
+            .unwrap();
+        assert_eq!(kp.public_key.modulus.bits(), 1024);
+    }
+
+    #[test]
+    fn generate_rsa_keypair_exponent() {
+        let mut builder = KeyPairBuilder::default();
+        builder
+            .with_rng(Box::new(StdRng::seed_from_u64(10)))
+            .with_key_size(KeySize::Bits1024);
+
+        let kp = builder.create_keypair().unwrap();
+        assert_eq!(
+            kp.public_key.public_exponent,
+            BigUint::from(DEFAULT_PUBLIC_EXPONENT)
+        );
+
+        // e = 3 divides lambda for roughly 3 in 4 pairs of primes, which are drawn again
+        for _ in 0..4 {
+            let kp = builder
+                .with_exponent(BigUint::from(3u32))
+                .create_keypair()
+                .unwrap();
+            let sk = kp.private_key;
+            let lambda = (&sk.prime1 - 1u32).lcm(&(&sk.prime2 - 1u32));
+            assert_eq!(sk.public_exponent, BigUint::from(3u32));
+            assert!(sk.public_exponent.gcd(&lambda).is_one());
+        }
+
+        let kp = builder.with_random_exponent().create_keypair().unwrap();
+        assert!(kp.public_key.public_exponent.bits() > 256);
+
+        let big_e = (BigUint::one() << 300u32) + 1u32;
+        assert!(builder
+            .with_exponent(big_e.clone())
+            .create_keypair()
+            .is_err());
+        assert!(builder
+            .with_exponent(big_e)
+            .with_max_exponent_bits(512)
+            .create_keypair()
+            .is_ok());
+    }
+
+    #[test]
+    fn generate_rsa_keypair_invalid_exponent() {
+        for e in [0u32, 1, 4, 65536] {
+            assert!(KeyPairBuilder::default()
+                .with_key_size(KeySize::Bits1024)
+                .with_exponent(BigUint::from(e))
+                .create_keypair()
+                .is_err());
+        }
+
+        // lambda(61 * 53) = lcm(60, 52) = 780, which isn't coprime to 3
+        assert!(KeyPairBuilder::default()
+            .with_modulus(BigUint::from(61u32), BigUint::from(53u32))
+            .with_exponent(BigUint::from(3u32))
+            .create_keypair()
+            .is_err());
+        assert!(KeyPairBuilder::default()
+            .with_modulus(BigUint::from(61u32), BigUint::from(53u32))
+            .with_exponent(BigUint::from(17u32))
+            .create_keypair()
+            .is_ok());
+    }
+
+    #[test]
+    fn generate_rsa_keypair_fips() {
+        let kp = KeyPairBuilder::default()
+            .with_rng(Box::new(StdRng::seed_from_u64(9)))
+            .with_fips_mode(true)
+            .create_keypair()
+            .unwrap();
+
+        let sk = kp.private_key;
+        let one = BigUint::one();
+
+        assert_eq!(sk.modulus.bits(), 2048);
+        assert_eq!(sk.public_exponent, BigUint::from(DEFAULT_PUBLIC_EXPONENT));
+
+        let p_q_bound = (&one << 2047u32).sqrt();
+        assert!(sk.prime1 > p_q_bound && sk.prime2 > p_q_bound);
+
+        let distance = if sk.prime1 > sk.prime2 {
+            &sk.prime1 - &sk.prime2
+        } else {
+            &sk.prime2 - &sk.prime1
+        };
+        assert!(distance > &one << 924u32);
+        assert!(sk.private_exponent > &one << 1024u32);
+    }
+
+    #[test]
+    fn generate_rsa_keypair_fips_primality() {
+        // a test that doesn't test anything can't weaken FIPS mode
+        struct AlwaysPrime;
+
+        impl PrimalityTest for AlwaysPrime {
+            fn is_prime(&self, _rng: &mut dyn RsaCsprng, _prime_candidate: &BigUint) -> bool {
+                true
+            }
+        }
+
+        let sk = KeyPairBuilder::default()
+            .with_rng(StdRng::seed_from_u64(9))
+            .with_fips_mode(true)
+            .with_primality_test(Box::new(AlwaysPrime))
+            .create_keypair()
+            .unwrap()
+            .private_key;
+        assert!(baillie_psw_is_prime(&sk.prime1) && baillie_psw_is_prime(&sk.prime2));
+
+        let (p, q) = (sk.prime1.clone(), sk.prime2.clone());
+        assert!(KeyPairBuilder::default()
+            .with_fips_mode(true)
+            .with_modulus(p.clone(), q.clone())
+            .create_keypair()
+            .is_ok());
+
+        // a specified modulus is tested too
+        let mut composite = &q + 2u32;
+        while baillie_psw_is_prime(&composite) {
+            composite += 2u32;
+        }
+        let res = KeyPairBuilder::default()
+            .with_fips_mode(true)
+            .with_modulus(p, composite)
+            .with_primality_test(Box::new(AlwaysPrime))
+            .create_keypair();
+        assert!(res.unwrap_err().to_string().contains("must be prime"));
+    }
+
+    #[test]
+    fn generate_rsa_keypair_fips_invalid_options() {
+        assert!(KeyPairBuilder::default()
+            .with_fips_mode(true)
+            .with_key_size(KeySize::Bits1024)
+            .create_keypair()
+            .is_err());
+
+        assert!(KeyPairBuilder::default()
+            .with_fips_mode(true)
+            .with_prime_count(3)
+            .create_keypair()
+            .is_err());
+
+        let one = BigUint::one();
+        for e in [
+            BigUint::from(3u32),
+            (&one << 16u32) + 1u32,
+            BigUint::from(65538u32),
+            (&one << 256u32) + 1u32,
+        ] {
+            let res = KeyPairBuilder::default()
+                .with_fips_mode(true)
+                .with_exponent(e.clone())
+                .create_keypair();
+            assert_eq!(res.is_ok(), e == (&one << 16u32) + 1u32);
+        }
+    }
+
     #[test]
     fn test_encryption_decryption() {
         let kp = default_keypair();
@@ -75,8 +594,6 @@ mod test {
 
         let d = decrypt_res.unwrap();
 
-        println!("{:?}", kp.private_key);
-
         assert_eq!(m, d);
     }
 
@@ -94,7 +611,7 @@ mod test {
         assert!(pk_deserial.is_ok());
         assert_eq!(pk, pk_deserial.unwrap());
         assert!(pem_deserial.is_ok());
-        assert_eq!(pk_serial, pem_deserial.unwrap());
+        assert_eq!(pk_serial, *pem_deserial.unwrap());
     }
 
     #[test]
@@ -112,4 +629,200 @@ mod test {
         assert!(pem_deserial.is_ok());
         assert_eq!(sk_serial, pem_deserial.unwrap());
     }
+
+    #[test]
+    fn test_private_key_from_primes() {
+        let sk = &default_keypair().private_key;
+        let (p, q, e) = (&sk.prime1, &sk.prime2, &sk.public_exponent);
+
+        // keys generated by this library use lambda(n), so the exact same key is rebuilt
+        let rebuilt =
+            RsaPrivateKey::from_primes(p.clone(), q.clone(), e.clone(), DerivationMode::Lambda)
+                .unwrap();
+        assert_eq!(rebuilt, *sk);
+
+        let phi = (p - 1u32) * (q - 1u32);
+        let rebuilt =
+            RsaPrivateKey::from_primes(p.clone(), q.clone(), e.clone(), DerivationMode::Phi)
+                .unwrap();
+        rebuilt.validate().unwrap();
+        assert_eq!(rebuilt.private_exponent, e.modinv(&phi).unwrap());
+        assert_eq!(rebuilt.exponent1, sk.exponent1);
+        assert_eq!(rebuilt.exponent2, sk.exponent2);
+        assert_eq!(rebuilt.coefficient, sk.coefficient);
+
+        let from_primes = |p: &BigUint, q: &BigUint, e: u32| {
+            RsaPrivateKey::from_primes(
+                p.clone(),
+                q.clone(),
+                BigUint::from(e),
+                DerivationMode::default(),
+            )
+        };
+
+        assert!(from_primes(p, p, 65537).is_err());
+        assert!(from_primes(p, &(q + 2u32), 65537).is_err());
+        assert!(from_primes(p, &(q >> 1), 65537).is_err());
+        assert!(from_primes(p, q, 65536).is_err());
+        assert!(from_primes(&BigUint::from(61u32), &BigUint::from(53u32), 17).is_err());
+
+        let other = KeyPairBuilder::default()
+            .with_rng(StdRng::seed_from_u64(19))
+            .with_exponent(BigUint::from(3u32))
+            .with_key_size(KeySize::Bits1024)
+            .create_keypair()
+            .unwrap()
+            .private_key;
+        // e = 3 divides p - 1 for roughly half of all primes
+        let p = loop {
+            let candidate = generate_candidate_prime(&mut rand::thread_rng(), 10, 512).unwrap();
+            if (&candidate - 1u32).is_multiple_of(&BigUint::from(3u32)) {
+                break candidate;
+            }
+        };
+        assert!(from_primes(&p, &other.prime2, 3).is_err());
+        assert!(from_primes(&other.prime1, &other.prime2, 3).is_ok());
+    }
+
+    #[test]
+    fn test_private_key_from_components() {
+        let sk = &default_keypair().private_key;
+        let (n, e, d) = (&sk.modulus, &sk.public_exponent, &sk.private_exponent);
+        let mut rng = StdRng::seed_from_u64(18);
+
+        let rebuilt =
+            RsaPrivateKey::from_components(&mut rng, n.clone(), e.clone(), d.clone()).unwrap();
+        rebuilt.validate().unwrap();
+        assert!(rebuilt.prime1 > rebuilt.prime2);
+        assert_eq!(&rebuilt.prime1 * &rebuilt.prime2, *n);
+        assert!(rebuilt.prime1 == sk.prime1 || rebuilt.prime1 == sk.prime2);
+        assert_eq!(rebuilt.private_exponent, *d);
+
+        // d computed modulo phi(n) instead of lambda(n)
+        let phi = (&sk.prime1 - 1u32) * (&sk.prime2 - 1u32);
+        let d_phi = e.modinv(&phi).unwrap();
+        let rebuilt =
+            RsaPrivateKey::from_components(&mut rng, n.clone(), e.clone(), d_phi).unwrap();
+        assert!(rebuilt.prime1 == sk.prime1 || rebuilt.prime1 == sk.prime2);
+
+        let ciphertext = default_keypair()
+            .public_key
+            .crypt(&BigUint::from(42u32))
+            .unwrap();
+        assert_eq!(
+            rebuilt.crypt(&ciphertext).unwrap(),
+            sk.crypt(&ciphertext).unwrap()
+        );
+
+        assert!(RsaPrivateKey::from_components(&mut rng, n.clone(), e.clone(), d + 2u32).is_err());
+        assert!(RsaPrivateKey::from_components(&mut rng, n + 1u32, e.clone(), d.clone()).is_err());
+        assert!(RsaPrivateKey::from_components(&mut rng, n.clone(), e.clone(), n.clone()).is_err());
+
+        // 7 * 1 - 1 is a multiple of lambda(65) / 2 = 6, but not of lambda(65)
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (n, e, d) = (BigUint::from(65u32), BigUint::from(7u32), BigUint::one());
+            assert!(RsaPrivateKey::from_components(&mut rng, n, e, d).is_err());
+        }
+    }
+
+    #[test]
+    fn test_private_key_zeroize() {
+        use zeroize::Zeroize;
+
+        let kp = multi_prime_keypair();
+
+        let mut sk = kp.private_key.clone();
+        sk.zeroize();
+
+        assert_eq!(sk.modulus, kp.private_key.modulus);
+        assert_eq!(sk.public_exponent, kp.private_key.public_exponent);
+        for secret in [
+            &sk.private_exponent,
+            &sk.prime1,
+            &sk.prime2,
+            &sk.exponent1,
+            &sk.exponent2,
+            &sk.coefficient,
+        ] {
+            assert_eq!(*secret, BigUint::ZERO);
+        }
+        for info in &sk.other_prime_infos {
+            assert_eq!(info.prime, BigUint::ZERO);
+            assert_eq!(info.exponent, BigUint::ZERO);
+            assert_eq!(info.coefficient, BigUint::ZERO);
+        }
+
+        // wiping a clone leaves the original intact
+        let m = BigUint::from(42u32);
+        let c = kp.public_key.crypt(&m).unwrap();
+        assert_eq!(kp.private_key.crypt(&c).unwrap(), m);
+    }
+
+    #[test]
+    fn test_key_validation() {
+        let kp = default_keypair();
+        kp.public_key.validate().unwrap();
+        kp.private_key.validate().unwrap();
+
+        multi_prime_keypair().private_key.validate().unwrap();
+
+        let public_error = |pk: RsaPublicKey| pk.validate().unwrap_err().to_string();
+        let private_error = |sk: RsaPrivateKey| sk.validate().unwrap_err().to_string();
+
+        let mut pk = kp.public_key.clone();
+        pk.modulus += 1u32;
+        assert!(public_error(pk).contains("modulus is even"));
+
+        let mut pk = kp.public_key.clone();
+        pk.modulus >>= 1100;
+        assert!(public_error(pk).contains("at least 1024 bits"));
+
+        for e in [1u32, 65536] {
+            let mut pk = kp.public_key.clone();
+            pk.public_exponent = BigUint::from(e);
+            assert!(public_error(pk).contains("3 <= e < n"));
+        }
+
+        let sk = &kp.private_key;
+
+        let mut bad = sk.clone();
+        bad.version = 1;
+        assert!(private_error(bad).contains("version 1"));
+
+        let mut bad = sk.clone();
+        bad.prime1 += 2u32;
+        let error = private_error(bad);
+        assert!(error.contains("r_1 is not prime") || error.contains("product of the primes"));
+
+        let mut bad = sk.clone();
+        bad.prime2 = bad.prime1.clone();
+        assert!(private_error(bad).contains("r_2 is repeated"));
+
+        let mut bad = sk.clone();
+        bad.private_exponent += 2u32;
+        assert!(private_error(bad).contains("e * d = 1 mod lambda(n)"));
+
+        let mut bad = sk.clone();
+        bad.exponent1 += 2u32;
+        assert!(private_error(bad).contains("exponent1"));
+
+        let mut bad = sk.clone();
+        bad.exponent2 += 2u32;
+        assert!(private_error(bad).contains("exponent2"));
+
+        let mut bad = sk.clone();
+        bad.coefficient += 1u32;
+        assert!(private_error(bad).contains("q * qInv = 1 mod p"));
+
+        let mut bad = multi_prime_keypair().private_key.clone();
+        bad.other_prime_infos[0].coefficient += 1u32;
+        assert!(private_error(bad).contains("coefficient of r_3"));
+
+        // a bad key that serializes fine is only caught by validation
+        let mut bad = sk.clone();
+        bad.exponent1 = &bad.exponent1 + (&bad.prime1 - 1u32);
+        let bad = rsa_private_key_der_deserialize(rsa_private_key_der_serialize(bad)).unwrap();
+        assert!(private_error(bad).contains("exponent1"));
+    }
 }
@@ -1,41 +1,220 @@
 //! Utility functions for hashing and mask generation
-use crate::errors::RsaError;
+use crate::errors::{RsaError, RsaErrorKind};
 use num::integer::div_ceil;
-use sha2::{Digest, Sha256};
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
+use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
 
+/// The hash functions supported by the mask generation function and the
+/// encoding schemes built on top of it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum HashType {
-    Sha2 = 256,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_224,
+    Sha512_256,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
 }
 
-/// [MGF1 - RFC8017](https://www.rfc-editor.org/rfc/rfc8017#appendix-B.2)
-/// Horribly slow? perhaps.
-pub fn mgf(hash_type: HashType, seed: u32, output_len: u32) -> Result<Vec<u8>, RsaError> {
-    let hash_len = hash_type as u32;
+impl HashType {
+    /// The length in octets of the hash output (`hLen` in RFC8017).
+    pub fn output_len(&self) -> usize {
+        match self {
+            HashType::Sha1 => 20,
+            HashType::Sha224 | HashType::Sha512_224 | HashType::Sha3_224 => 28,
+            HashType::Sha256 | HashType::Sha512_256 | HashType::Sha3_256 => 32,
+            HashType::Sha384 | HashType::Sha3_384 => 48,
+            HashType::Sha512 | HashType::Sha3_512 => 64,
+        }
+    }
+
+    /// Hashes `data` with the selected hash function.
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashType::Sha1 => Sha1::digest(data).to_vec(),
+            HashType::Sha224 => Sha224::digest(data).to_vec(),
+            HashType::Sha256 => Sha256::digest(data).to_vec(),
+            HashType::Sha384 => Sha384::digest(data).to_vec(),
+            HashType::Sha512 => Sha512::digest(data).to_vec(),
+            HashType::Sha512_224 => Sha512_224::digest(data).to_vec(),
+            HashType::Sha512_256 => Sha512_256::digest(data).to_vec(),
+            HashType::Sha3_224 => Sha3_224::digest(data).to_vec(),
+            HashType::Sha3_256 => Sha3_256::digest(data).to_vec(),
+            HashType::Sha3_384 => Sha3_384::digest(data).to_vec(),
+            HashType::Sha3_512 => Sha3_512::digest(data).to_vec(),
+        }
+    }
+}
+
+/// A mask generation function, as described in
+/// [Appendix B.2 of RFC8017](https://www.rfc-editor.org/rfc/rfc8017#appendix-B.2).
+///
+/// A mask generation function takes an octet string of variable length and
+/// a desired output length, and deterministically produces an octet string
+/// of the desired length.
+pub trait MaskGenerationFunction {
+    fn generate_mask(&self, seed: &[u8], mask_len: usize) -> Result<Vec<u8>, RsaError>;
+}
+
+/// A reference to a mask generation function is one too, so `&dyn MaskGenerationFunction`
+/// can be used wherever a mask generation function is expected.
+impl<M: MaskGenerationFunction + ?Sized> MaskGenerationFunction for &M {
+    fn generate_mask(&self, seed: &[u8], mask_len: usize) -> Result<Vec<u8>, RsaError> {
+        (**self).generate_mask(seed, mask_len)
+    }
+}
+
+/// The MGF1 mask generation function, based on the given hash function.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Mgf1 {
+    pub hash: HashType,
+}
 
-    if output_len * hash_len > u32::MAX {
+impl Mgf1 {
+    pub fn new(hash: HashType) -> Self {
+        Self { hash }
+    }
+}
+
+impl MaskGenerationFunction for Mgf1 {
+    fn generate_mask(&self, seed: &[u8], mask_len: usize) -> Result<Vec<u8>, RsaError> {
+        mgf(self.hash, seed, mask_len)
+    }
+}
+
+/// [MGF1 - RFC8017](https://www.rfc-editor.org/rfc/rfc8017#appendix-B.2.1)
+///
+/// Produces `output_len` octets by hashing `seed || C` for each 4-octet big-endian
+/// counter `C = 0, 1, ...`, and truncating the concatenation of the outputs.
+/// Fails if `output_len > 2^32 * hLen`.
+pub fn mgf(hash_type: HashType, seed: &[u8], output_len: usize) -> Result<Vec<u8>, RsaError> {
+    let hash_len = hash_type.output_len();
+
+    if output_len as u64 > (1u64 << 32) * hash_len as u64 {
         return Err(RsaError::new(
-            crate::errors::RsaErrorKind::MaskGenerationFunctionError,
+            RsaErrorKind::MaskGenerationFunctionError,
             format!("output_len {} too large!", output_len),
         ));
     }
 
-    let mut t: Vec<u8> = Vec::new();
-    // let cast_seed = BigUint::from_u32(seed).unwrap();
-    let mut seed = Vec::from(seed.to_le_bytes());
-
-    for i in 0..div_ceil(output_len, hash_len) {
-        let last = seed.len() - 1;
-        seed.extend(i.to_le_bytes());
+    let mut t: Vec<u8> = Vec::with_capacity(output_len + hash_len);
+    let mut input = Vec::with_capacity(seed.len() + 4);
 
-        let hash = match hash_type {
-            HashType::Sha2 => Sha256::digest(&seed),
-        };
-        t.extend(hash);
+    for counter in 0..div_ceil(output_len as u64, hash_len as u64) {
+        input.clear();
+        input.extend_from_slice(seed);
+        input.extend((counter as u32).to_be_bytes());
 
-        seed.drain(last..);
+        t.extend(hash_type.digest(&input));
     }
 
-    t.drain(output_len as usize - 1..);
+    t.truncate(output_len);
 
     Ok(t)
 }
+
+/// SHAKE256 used as a mask generation function, as RFC8702 does for RSASSA-PSS.
+/// Used to test that mask generation functions other than MGF1 can be plugged in.
+#[cfg(test)]
+pub(crate) struct Shake256Mgf;
+
+#[cfg(test)]
+impl MaskGenerationFunction for Shake256Mgf {
+    fn generate_mask(&self, seed: &[u8], mask_len: usize) -> Result<Vec<u8>, RsaError> {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+
+        let mut hasher = sha3::Shake256::default();
+        hasher.update(seed);
+
+        let mut mask = vec![0u8; mask_len];
+        hasher.finalize_xof().read(&mut mask);
+
+        Ok(mask)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_mgf1_vectors() {
+        let sha1 = Mgf1::new(HashType::Sha1);
+        assert_eq!(to_hex(&sha1.generate_mask(b"foo", 3).unwrap()), "1ac907");
+        assert_eq!(
+            to_hex(&sha1.generate_mask(b"bar", 5).unwrap()),
+            "bc0c655e01"
+        );
+        assert_eq!(
+            to_hex(&sha1.generate_mask(b"bar", 50).unwrap()),
+            "bc0c655e016bc2931d85a2e675181adcef7f581f76df2739da74faac41627be2\
+             f7f415c89e983fd0ce80ced9878641cb4876"
+        );
+
+        let sha256 = Mgf1::new(HashType::Sha256);
+        assert_eq!(
+            to_hex(&sha256.generate_mask(b"bar", 50).unwrap()),
+            "382576a7841021cc28fc4c0948753fb8312090cea942ea4c4e735d10dc724b15\
+             5f9f6069f289d61daca0cb814502ef04eae1"
+        );
+
+        let sha3_256 = Mgf1::new(HashType::Sha3_256);
+        assert_eq!(
+            to_hex(&sha3_256.generate_mask(b"bar", 50).unwrap()),
+            "03409e2e3e7c3047586c9c69aeb18d6366861a9a8adb8a62baa683e620586ded\
+             428b483736d1a1aa83413d822e746f91b249"
+        );
+    }
+
+    #[test]
+    fn test_mgf1_lengths() {
+        let hashes = [
+            HashType::Sha1,
+            HashType::Sha224,
+            HashType::Sha256,
+            HashType::Sha384,
+            HashType::Sha512,
+            HashType::Sha512_224,
+            HashType::Sha512_256,
+            HashType::Sha3_224,
+            HashType::Sha3_256,
+            HashType::Sha3_384,
+            HashType::Sha3_512,
+        ];
+
+        for hash in hashes {
+            let h_len = hash.output_len();
+            assert_eq!(hash.digest(b"").len(), h_len);
+
+            assert!(mgf(hash, b"seed", 0).unwrap().is_empty());
+            for len in [1, h_len, h_len + 1, 3 * h_len - 1] {
+                let mask = mgf(hash, b"seed", len).unwrap();
+                assert_eq!(mask.len(), len);
+
+                // the first block is always Hash(seed || 0x00000000)
+                let first = hash.digest(b"seed\x00\x00\x00\x00");
+                assert_eq!(mask[..len.min(h_len)], first[..len.min(h_len)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mgf1_output_too_long() {
+        // 2^32 * hLen + 1 octets doesn't fit in a usize on 32-bit targets
+        let Ok(too_long) = usize::try_from((1u64 << 32) * HashType::Sha1.output_len() as u64 + 1)
+        else {
+            return;
+        };
+        assert!(mgf(HashType::Sha1, b"seed", too_long).is_err());
+    }
+}
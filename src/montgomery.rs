@@ -0,0 +1,517 @@
+//! # Montgomery Arithmetic
+//! Modular exponentiation for the RSA primitives, on the Montgomery parameters of each
+//! modulus, which a key computes once and keeps (see `crypto::Precomputed`).
+//!
+//! By default, the exponentiation runs on the 64-bit limbs of the modulus with a fixed
+//! 4-bit window, much like `num-bigint`'s own, but without computing the parameters
+//! or allocating on every operation. Its timing depends on the exponent and the base.
+//!
+//! With the `constant-time` feature, it's built on the fixed-width, constant-time Montgomery
+//! arithmetic of `crypto-bigint` instead. The exponentiations and the CRT recombination
+//! of a private key operation then don't branch on or index memory by their inputs.
+//! What's left to `num-bigint`, and so isn't constant-time, is:
+//! - converting values in and out, since keys still store them as `BigUint`s; this shows
+//!   the byte lengths of the values
+//! - blinding the ciphertext and unblinding the result, which are multiplications mod `n`
+//! - the non-CRT fallback of `FaultRecovery::NonCrt`, beyond the exponentiation itself
+#[cfg(feature = "constant-time")]
+use crypto_bigint::{
+    modular::{BoxedMontyForm, BoxedMontyParams},
+    BoxedUint, Odd, Resize,
+};
+use num::BigUint;
+use zeroize::Zeroize;
+
+#[cfg(not(feature = "constant-time"))]
+use crate::util::zeroize_biguint;
+
+/// The Montgomery parameters (`R mod m`, `R^2 mod m` and `-m^(-1) mod 2^64`) of an odd modulus.
+#[cfg(not(feature = "constant-time"))]
+#[derive(Clone)]
+pub(crate) struct Modulus {
+    modulus: BigUint,
+    /// The 64-bit limbs of the modulus, least significant first. `R = 2^(64 * limbs)`
+    limbs: Vec<u64>,
+    /// `R mod m`, which is 1 in Montgomery form
+    one: Vec<u64>,
+    /// `R^2 mod m`
+    r2: Vec<u64>,
+    /// `-m^(-1) mod 2^64`
+    m_inv: u64,
+}
+
+#[cfg(not(feature = "constant-time"))]
+impl Modulus {
+    /// Precomputes the Montgomery parameters of `modulus`, or returns `None` if it's even.
+    pub(crate) fn new(modulus: &BigUint) -> Option<Self> {
+        if !modulus.bit(0) {
+            return None;
+        }
+
+        let limbs: Vec<u64> = modulus.iter_u64_digits().collect();
+
+        // every odd number is its own inverse mod 2^3, and each step of Newton's
+        // iteration doubles the number of correct bits
+        let mut inv = limbs[0];
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inv)));
+        }
+
+        let r = BigUint::from(1u32) << (64 * limbs.len());
+        let mut one = &r % modulus;
+        let mut r2 = &one * &one % modulus;
+
+        let res = Self {
+            modulus: modulus.clone(),
+            one: to_limbs(&one, limbs.len()),
+            r2: to_limbs(&r2, limbs.len()),
+            limbs,
+            m_inv: inv.wrapping_neg(),
+        };
+        zeroize_biguint(&mut one);
+        zeroize_biguint(&mut r2);
+
+        Some(res)
+    }
+
+    /// Whether these are the parameters of `modulus`.
+    pub(crate) fn is_for(&self, modulus: &BigUint) -> bool {
+        *modulus == self.modulus
+    }
+
+    /// `base^exponent mod m`.
+    pub(crate) fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        self.pow_with(base, exponent, 4, false)
+    }
+
+    /// `base^exponent mod m` for a public `exponent`. Windows of zeros are skipped, and
+    /// the bits of a short exponent, like 65537, are taken one at a time rather than
+    /// computing a table of powers first.
+    pub(crate) fn pow_vartime(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let window = if exponent.bits() < 64 { 1 } else { 4 };
+        self.pow_with(base, exponent, window, true)
+    }
+
+    /// `base^exponent mod m` with fixed windows of `window` bits, which divides 64.
+    fn pow_with(
+        &self,
+        base: &BigUint,
+        exponent: &BigUint,
+        window: usize,
+        skip_zeros: bool,
+    ) -> BigUint {
+        let len = self.limbs.len();
+        // every product is computed in `t`, and its reduction left in `t[len..2 * len]`
+        let mut t = vec![0u64; 2 * len];
+
+        // x = base * R mod m
+        let mut reduced = base % &self.modulus;
+        let mut x = to_limbs(&reduced, len);
+        zeroize_biguint(&mut reduced);
+        self.mul(&x, &self.r2, &mut t);
+        x.copy_from_slice(&t[len..]);
+
+        // table[i] = x^i, in Montgomery form
+        let mut table = vec![0u64; (1 << window) * len];
+        table[..len].copy_from_slice(&self.one);
+        for i in 1..1 << window {
+            self.mul(&table[(i - 1) * len..i * len], &x, &mut t);
+            table[i * len..(i + 1) * len].copy_from_slice(&t[len..]);
+        }
+
+        let mut digits: Vec<u64> = exponent.iter_u64_digits().collect();
+        let mut acc = self.one.clone();
+        let windows = exponent.bits().div_ceil(window as u64) as usize;
+        for bit in (0..windows).rev().map(|w| w * window) {
+            for _ in 0..window {
+                self.square(&acc, &mut t);
+                acc.copy_from_slice(&t[len..]);
+            }
+
+            let i = (digits[bit / 64] >> (bit % 64)) as usize & ((1 << window) - 1);
+            if i != 0 || !skip_zeros {
+                self.mul(&acc, &table[i * len..(i + 1) * len], &mut t);
+                acc.copy_from_slice(&t[len..]);
+            }
+        }
+
+        // out of Montgomery form: acc * R^(-1)
+        t[..len].copy_from_slice(&acc);
+        t[len..].fill(0);
+        self.reduce(&mut t);
+
+        let mut words: Vec<u32> = t[len..]
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect();
+        let res = BigUint::from_slice(&words);
+
+        for limbs in [&mut t, &mut x, &mut table, &mut digits, &mut acc] {
+            limbs.zeroize();
+        }
+        words.zeroize();
+
+        res
+    }
+
+    /// Montgomery multiplication: `a * b * R^(-1) mod m`, for `a, b < m`.
+    fn mul(&self, a: &[u64], b: &[u64], t: &mut [u64]) {
+        let len = a.len();
+        t.fill(0);
+
+        for (i, &a_i) in a.iter().enumerate() {
+            t[i + len] = mul_add(&mut t[i..i + len], b, a_i);
+        }
+
+        self.reduce(t);
+    }
+
+    /// Montgomery squaring: `a * a * R^(-1) mod m`, for `a < m`.
+    fn square(&self, a: &[u64], t: &mut [u64]) {
+        let len = a.len();
+        t.fill(0);
+
+        // the products a_i * a_j for i < j, which each appear twice in the square
+        for (i, &a_i) in a.iter().enumerate().take(len - 1) {
+            t[i + len] = mul_add(&mut t[2 * i + 1..i + len], &a[i + 1..], a_i);
+        }
+
+        let mut carry = 0u64;
+        for t_i in t.iter_mut() {
+            let next = *t_i >> 63;
+            *t_i = (*t_i << 1) | carry;
+            carry = next;
+        }
+
+        let mut carry = 0u64;
+        for (i, &a_i) in a.iter().enumerate() {
+            let x = a_i as u128 * a_i as u128;
+            let lo = t[2 * i] as u128 + (x as u64) as u128 + carry as u128;
+            t[2 * i] = lo as u64;
+            let hi = t[2 * i + 1] as u128 + (x >> 64) + (lo >> 64);
+            t[2 * i + 1] = hi as u64;
+            carry = (hi >> 64) as u64;
+        }
+
+        self.reduce(t);
+    }
+
+    /// Montgomery reduction of `t < m * R`: leaves `t * R^(-1) mod m` in `t[len..]`.
+    fn reduce(&self, t: &mut [u64]) {
+        let m = &self.limbs;
+        let len = m.len();
+
+        // t = t + m * u_i * 2^(64 * i), with u_i chosen to clear the limb t_i
+        let mut top = 0u64;
+        for i in 0..len {
+            let u = t[i].wrapping_mul(self.m_inv);
+            let carry = mul_add(&mut t[i..i + len], m, u);
+
+            let x = t[i + len] as u128 + carry as u128 + top as u128;
+            t[i + len] = x as u64;
+            top = (x >> 64) as u64;
+        }
+
+        // t < 2m, so subtracting m once is enough
+        let res = &mut t[len..];
+        if top != 0 || res.iter().rev().ge(m.iter().rev()) {
+            let mut borrow = false;
+            for (r_j, &m_j) in res.iter_mut().zip(m) {
+                let (x, b_1) = r_j.overflowing_sub(m_j);
+                let (x, b_2) = x.overflowing_sub(borrow as u64);
+                *r_j = x;
+                borrow = b_1 || b_2;
+            }
+        }
+    }
+}
+
+/// `t = t + a * b`, returning the limb carried out of `t`, which has as many limbs as `a`.
+#[cfg(not(feature = "constant-time"))]
+fn mul_add(t: &mut [u64], a: &[u64], b: u64) -> u64 {
+    let mut carry = 0u64;
+    for (t_j, &a_j) in t.iter_mut().zip(a) {
+        let x = *t_j as u128 + a_j as u128 * b as u128 + carry as u128;
+        *t_j = x as u64;
+        carry = (x >> 64) as u64;
+    }
+
+    carry
+}
+
+/// The parameters of a prime are as secret as the prime.
+#[cfg(not(feature = "constant-time"))]
+impl Drop for Modulus {
+    fn drop(&mut self) {
+        zeroize_biguint(&mut self.modulus);
+        for limbs in [&mut self.limbs, &mut self.one, &mut self.r2] {
+            limbs.zeroize();
+        }
+    }
+}
+
+/// The limbs of `x < 2^(64 * len)`, least significant first.
+#[cfg(not(feature = "constant-time"))]
+fn to_limbs(x: &BigUint, len: usize) -> Vec<u64> {
+    let mut limbs: Vec<u64> = x.iter_u64_digits().collect();
+    limbs.resize(len, 0);
+    limbs
+}
+
+/// The Montgomery parameters (`R mod m`, `R^2 mod m` and `-m^(-1) mod 2^64`) of an odd modulus.
+#[cfg(feature = "constant-time")]
+#[derive(Clone, Debug)]
+pub(crate) struct Modulus {
+    params: BoxedMontyParams,
+}
+
+#[cfg(feature = "constant-time")]
+impl Modulus {
+    /// Precomputes the Montgomery parameters of `modulus`, or returns `None` if it's even.
+    pub(crate) fn new(modulus: &BigUint) -> Option<Self> {
+        let bits = modulus.bits().max(1) as u32;
+        let modulus = Odd::new(to_boxed_uint(modulus, bits)).into_option()?;
+
+        Some(Self {
+            params: BoxedMontyParams::new(modulus),
+        })
+    }
+
+    /// The modulus itself.
+    fn value(&self) -> &BoxedUint {
+        self.params.modulus().as_ref()
+    }
+
+    /// Whether these are the parameters of `modulus`, comparing its digits
+    /// with the limbs of the modulus in place.
+    pub(crate) fn is_for(&self, modulus: &BigUint) -> bool {
+        #[cfg(target_pointer_width = "64")]
+        let mut digits = modulus.iter_u64_digits();
+        #[cfg(not(target_pointer_width = "64"))]
+        let mut digits = modulus.iter_u32_digits();
+
+        let words = self.value().as_words();
+        digits.len() <= words.len() && words.iter().all(|&w| w == digits.next().unwrap_or(0))
+    }
+
+    /// `base^exponent mod m`, in time that only depends on the bit lengths of
+    /// the modulus and of `base`, not on the value of either input.
+    pub(crate) fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        from_boxed_uint(self.pow_boxed(base, exponent))
+    }
+
+    /// Like `pow`, but leaves the result at the precision of the modulus.
+    fn pow_boxed(&self, base: &BigUint, exponent: &BigUint) -> BoxedUint {
+        let bits = self.params.bits_precision().max(exponent.bits() as u32);
+        let mut exponent = to_boxed_uint(exponent, bits);
+
+        let res = self.pow_with(base, |x| x.pow(&exponent));
+        exponent.zeroize();
+
+        res
+    }
+
+    /// `base^exponent mod m` for a public `exponent`, whose bit length shows in the timing.
+    pub(crate) fn pow_vartime(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let bits = exponent.bits().max(1) as u32;
+        let exponent = to_boxed_uint(exponent, bits);
+
+        from_boxed_uint(self.pow_with(base, |x| x.pow_bounded_exp(&exponent, bits)))
+    }
+
+    fn pow_with(
+        &self,
+        base: &BigUint,
+        pow: impl Fn(&BoxedMontyForm) -> BoxedMontyForm,
+    ) -> BoxedUint {
+        let mut base = to_boxed_uint(base, base.bits().max(1) as u32);
+        let mut reduced = base.rem(self.params.modulus().as_nz_ref());
+        base.zeroize();
+
+        let mut x = BoxedMontyForm::new(reduced.clone(), &self.params);
+        reduced.zeroize();
+
+        let mut y = pow(&x);
+        x.zeroize();
+
+        let res = y.retrieve();
+        y.zeroize();
+
+        res
+    }
+}
+
+/// `base^d mod n` from the CRT values of a private key, like `RsaPrivateKey::crt_crypt`,
+/// with the recombination done at the fixed precision of `n`:
+/// starting from `m = base^dQ mod q` and `R = q`, each of `(p, dP, qInv)`, `(r_3, d_3, t_3)`, ...
+/// is folded in with `h = (m_i - m) * t_i mod r_i`, `m = m + R * h` and `R = R * r_i`.
+///
+/// `moduli` are those of `n, p, q, r_3, ..., r_u`, `exponents` are `dP, dQ, d_3, ..., d_u`,
+/// and `coefficients` are `qInv, t_3, ..., t_u`.
+#[cfg(feature = "constant-time")]
+pub(crate) fn crt_pow(
+    moduli: &[&Modulus],
+    base: &BigUint,
+    exponents: &[&BigUint],
+    coefficients: &[&BigUint],
+) -> BigUint {
+    let bits = moduli[0].params.bits_precision();
+
+    let mut m_2 = moduli[2].pow_boxed(base, exponents[1]);
+    let mut m = (&m_2).resize_unchecked(bits);
+    let mut r = moduli[2].value().resize_unchecked(bits);
+    m_2.zeroize();
+
+    let folds = [(moduli[1], exponents[0], coefficients[0])]
+        .into_iter()
+        .chain((3..moduli.len()).map(|i| (moduli[i], exponents[i - 1], coefficients[i - 2])));
+    for (modulus, exponent, coefficient) in folds {
+        let prime = modulus.params.modulus().as_nz_ref();
+
+        let mut m_i = modulus.pow_boxed(base, exponent);
+        let mut m_r = m.rem(prime);
+        let mut t_i = to_boxed_uint(coefficient, modulus.params.bits_precision());
+
+        let mut diff = m_i.sub_mod(&m_r, prime);
+        let mut h = diff.mul_mod(&t_i, prime);
+        let mut h_wide = (&h).resize_unchecked(bits);
+        let mut rh = r.wrapping_mul(&h_wide);
+        m.wrapping_add_assign(&rh);
+
+        let mut next_r = r.wrapping_mul(modulus.value());
+        std::mem::swap(&mut r, &mut next_r);
+
+        for x in [
+            &mut m_i,
+            &mut m_r,
+            &mut t_i,
+            &mut diff,
+            &mut h,
+            &mut h_wide,
+            &mut rh,
+            &mut next_r,
+        ] {
+            x.zeroize();
+        }
+    }
+    r.zeroize();
+
+    from_boxed_uint(m)
+}
+
+/// Converts `x` back into a `BigUint`, wiping it.
+#[cfg(feature = "constant-time")]
+fn from_boxed_uint(mut x: BoxedUint) -> BigUint {
+    let mut bytes = x.to_be_bytes();
+    x.zeroize();
+
+    let res = BigUint::from_bytes_be(&bytes);
+    bytes.zeroize();
+
+    res
+}
+
+/// Converts `x` into a `BoxedUint` of at least `bits` bits of precision.
+#[cfg(feature = "constant-time")]
+fn to_boxed_uint(x: &BigUint, bits: u32) -> BoxedUint {
+    let mut bytes = x.to_bytes_be();
+    let res = BoxedUint::from_be_slice_truncated(&bytes, bits);
+    bytes.zeroize();
+
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num::{One, Zero};
+    use num_bigint::RandBigInt;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_montgomery_pow() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for bits in [3, 64, 65, 127, 512, 1000, 1024] {
+            let mut m = rng.gen_biguint(bits);
+            m.set_bit(bits - 1, true);
+            m.set_bit(0, true);
+
+            let modulus = Modulus::new(&m).unwrap();
+
+            for _ in 0..5 {
+                // bases both below and above the modulus
+                let base = rng.gen_biguint(bits + 64);
+                let exp = rng.gen_biguint(bits);
+
+                assert_eq!(modulus.pow(&base, &exp), base.modpow(&exp, &m));
+                assert_eq!(modulus.pow_vartime(&base, &exp), base.modpow(&exp, &m));
+
+                let small = &base % &m;
+                assert_eq!(modulus.pow(&small, &exp), small.modpow(&exp, &m));
+            }
+
+            let three = BigUint::from(3u32);
+            assert!(modulus.pow(&BigUint::zero(), &three).is_zero());
+            assert!(modulus.pow(&three, &BigUint::zero()) == BigUint::one() % &m);
+            assert_eq!(
+                modulus.pow_vartime(&three, &BigUint::from(65537u32)),
+                three.modpow(&BigUint::from(65537u32), &m)
+            );
+        }
+
+        assert!(Modulus::new(&BigUint::from(1025u32))
+            .unwrap()
+            .is_for(&BigUint::from(1025u32)));
+        assert!(!Modulus::new(&BigUint::from(1025u32))
+            .unwrap()
+            .is_for(&BigUint::from(1027u32)));
+        assert!(!Modulus::new(&BigUint::from(1025u32))
+            .unwrap()
+            .is_for(&((BigUint::one() << 100) + 1025u32)));
+        assert!(Modulus::new(&BigUint::from(1024u32)).is_none());
+        assert!(Modulus::new(&BigUint::zero()).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "constant-time")]
+    fn test_crt_pow() {
+        use crate::keygen::{KeyPairBuilder, KeySize};
+
+        for prime_count in [2, 3] {
+            let sk = KeyPairBuilder::default()
+                .with_rng(StdRng::seed_from_u64(prime_count as u64))
+                .with_key_size(KeySize::Bits1024)
+                .with_prime_count(prime_count)
+                .create_keypair()
+                .unwrap()
+                .private_key;
+
+            let primes = [&sk.prime1, &sk.prime2]
+                .into_iter()
+                .chain(sk.other_prime_infos.iter().map(|info| &info.prime));
+            let moduli: Vec<Modulus> = std::iter::once(&sk.modulus)
+                .chain(primes)
+                .map(|m| Modulus::new(m).unwrap())
+                .collect();
+            let moduli: Vec<&Modulus> = moduli.iter().collect();
+
+            let exponents: Vec<&BigUint> = [&sk.exponent1, &sk.exponent2]
+                .into_iter()
+                .chain(sk.other_prime_infos.iter().map(|info| &info.exponent))
+                .collect();
+            let coefficients: Vec<&BigUint> = std::iter::once(&sk.coefficient)
+                .chain(sk.other_prime_infos.iter().map(|info| &info.coefficient))
+                .collect();
+
+            let mut rng = StdRng::seed_from_u64(0);
+            for _ in 0..5 {
+                let c = rng.gen_biguint_below(&sk.modulus);
+                assert_eq!(
+                    crt_pow(&moduli, &c, &exponents, &coefficients),
+                    c.modpow(&sk.private_exponent, &sk.modulus)
+                );
+            }
+        }
+    }
+}
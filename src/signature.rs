@@ -0,0 +1,630 @@
+//! # Signatures
+//! This module implements the signature schemes with appendix defined in
+//! [Section 8 of RFC8017](https://www.rfc-editor.org/rfc/rfc8017#section-8).
+
+use subtle::ConstantTimeEq;
+
+use crate::{
+    crypto::{xor_in_place, RsaPrimitive},
+    errors::{RsaError, RsaErrorKind},
+    keygen::{RsaCsprng, RsaPrivateKey, RsaPublicKey},
+    mask::{HashType, MaskGenerationFunction, Mgf1},
+    serial::digest_info_der_serialize,
+    util::{i2osp, os2ip},
+};
+
+/// The length of the salt used by RSASSA-PSS.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SaltLength {
+    /// The salt is as long as the output of the hash function. This is the most common choice.
+    Digest,
+    /// The salt is exactly this many octets long.
+    Fixed(usize),
+    /// When verifying, the salt length is recovered from the encoded message.
+    /// When signing, the largest salt length permitted by the modulus is used.
+    Auto,
+}
+
+/// The hash function, mask generation function and salt length used by RSASSA-PSS.
+///
+/// By default, SHA-256 is used both for hashing the message and within MGF1,
+/// and the salt is as long as the digest.
+/// Another mask generation function can be used with `PssParams::with_mgf`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PssParams<M: MaskGenerationFunction = Mgf1> {
+    /// The hash function applied to the message.
+    pub hash: HashType,
+    /// The mask generation function.
+    pub mgf: M,
+    /// The length of the salt.
+    pub salt_len: SaltLength,
+}
+
+impl Default for PssParams {
+    fn default() -> Self {
+        Self::new(HashType::Sha256, HashType::Sha256, SaltLength::Digest)
+    }
+}
+
+impl PssParams {
+    /// Uses MGF1 with `mgf_hash` as the mask generation function.
+    pub fn new(hash: HashType, mgf_hash: HashType, salt_len: SaltLength) -> Self {
+        Self::with_mgf(hash, Mgf1::new(mgf_hash), salt_len)
+    }
+}
+
+impl<M: MaskGenerationFunction> PssParams<M> {
+    pub fn with_mgf(hash: HashType, mgf: M, salt_len: SaltLength) -> Self {
+        Self {
+            hash,
+            mgf,
+            salt_len,
+        }
+    }
+}
+
+/// [RSASSA-PSS-SIGN](https://www.rfc-editor.org/rfc/rfc8017#section-8.1.1)
+pub trait RsaPssSign {
+    /// Signs `message`, drawing the salt from `rng`.
+    ///
+    /// The resulting signature is always exactly as long as the modulus.
+    fn sign_pss<R: RsaCsprng + ?Sized, M: MaskGenerationFunction>(
+        &self,
+        rng: &mut R,
+        params: &PssParams<M>,
+        message: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, RsaError>;
+}
+
+/// [RSASSA-PSS-VERIFY](https://www.rfc-editor.org/rfc/rfc8017#section-8.1.2)
+pub trait RsaPssVerify {
+    /// Verifies that `signature` is a valid signature of `message`.
+    ///
+    /// Returns an `invalid signature` error if it isn't.
+    fn verify_pss<M: MaskGenerationFunction>(
+        &self,
+        params: &PssParams<M>,
+        message: impl AsRef<[u8]>,
+        signature: impl AsRef<[u8]>,
+    ) -> Result<(), RsaError>;
+}
+
+/// [RSASSA-PKCS1-v1_5-SIGN](https://www.rfc-editor.org/rfc/rfc8017#section-8.2.1)
+pub trait RsaPkcs1v15Sign {
+    /// Signs `message`, which is hashed with `hash`.
+    ///
+    /// Signing is deterministic, and the resulting signature is always exactly as long
+    /// as the modulus.
+    fn sign_pkcs1v15(&self, hash: HashType, message: impl AsRef<[u8]>)
+        -> Result<Vec<u8>, RsaError>;
+}
+
+/// [RSASSA-PKCS1-v1_5-VERIFY](https://www.rfc-editor.org/rfc/rfc8017#section-8.2.2)
+pub trait RsaPkcs1v15Verify {
+    /// Verifies that `signature` is a valid signature of `message`, which is hashed with `hash`.
+    ///
+    /// The encoded message recovered from the signature is never parsed; it is compared
+    /// in constant time against a fresh encoding of `message`.
+    /// Returns an `invalid signature` error if the two differ.
+    fn verify_pkcs1v15(
+        &self,
+        hash: HashType,
+        message: impl AsRef<[u8]>,
+        signature: impl AsRef<[u8]>,
+    ) -> Result<(), RsaError>;
+}
+
+impl RsaPssSign for RsaPrivateKey {
+    fn sign_pss<R: RsaCsprng + ?Sized, M: MaskGenerationFunction>(
+        &self,
+        rng: &mut R,
+        params: &PssParams<M>,
+        message: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, RsaError> {
+        let em_bits = self.modulus.bits() as usize - 1;
+        let em_len = em_bits.div_ceil(8);
+        let h_len = params.hash.output_len();
+
+        let s_len = match params.salt_len {
+            SaltLength::Digest => h_len,
+            SaltLength::Fixed(len) => len,
+            SaltLength::Auto => em_len.saturating_sub(h_len + 2),
+        };
+
+        let mut salt = vec![0u8; s_len];
+        rng.fill_bytes(&mut salt);
+
+        let m_hash = params.hash.digest(message.as_ref());
+        let em = emsa_pss_encode(params.hash, &params.mgf, &m_hash, &salt, em_bits)?;
+
+        // RSASP1 is the same operation as RSADP
+        self.crypt_with_bytes(&em)
+    }
+}
+
+impl RsaPssVerify for RsaPublicKey {
+    fn verify_pss<M: MaskGenerationFunction>(
+        &self,
+        params: &PssParams<M>,
+        message: impl AsRef<[u8]>,
+        signature: impl AsRef<[u8]>,
+    ) -> Result<(), RsaError> {
+        let signature = signature.as_ref();
+        let em_bits = self.modulus.bits() as usize - 1;
+
+        if signature.len() != self.modulus_len() {
+            return Err(invalid_signature());
+        }
+
+        // RSAVP1 is the same operation as RSAEP
+        let m = self
+            .crypt(&os2ip(signature))
+            .map_err(|_| invalid_signature())?;
+        let em = i2osp(&m, em_bits.div_ceil(8)).map_err(|_| invalid_signature())?;
+
+        let m_hash = params.hash.digest(message.as_ref());
+
+        let s_len = match params.salt_len {
+            SaltLength::Digest => Some(params.hash.output_len()),
+            SaltLength::Fixed(len) => Some(len),
+            SaltLength::Auto => None,
+        };
+
+        if emsa_pss_verify(params.hash, &params.mgf, s_len, &m_hash, &em, em_bits) {
+            Ok(())
+        } else {
+            Err(invalid_signature())
+        }
+    }
+}
+
+impl RsaPkcs1v15Sign for RsaPrivateKey {
+    fn sign_pkcs1v15(
+        &self,
+        hash: HashType,
+        message: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, RsaError> {
+        let em = emsa_pkcs1v15_encode(hash, message.as_ref(), self.modulus_len())?;
+
+        // RSASP1 is the same operation as RSADP
+        self.crypt_with_bytes(&em)
+    }
+}
+
+impl RsaPkcs1v15Verify for RsaPublicKey {
+    fn verify_pkcs1v15(
+        &self,
+        hash: HashType,
+        message: impl AsRef<[u8]>,
+        signature: impl AsRef<[u8]>,
+    ) -> Result<(), RsaError> {
+        let signature = signature.as_ref();
+        let k = self.modulus_len();
+
+        if signature.len() != k {
+            return Err(invalid_signature());
+        }
+
+        // RSAVP1 is the same operation as RSAEP
+        let em = self
+            .crypt_with_bytes(signature)
+            .map_err(|_| invalid_signature())?;
+        let em_prime = emsa_pkcs1v15_encode(hash, message.as_ref(), k)?;
+
+        if bool::from(em.ct_eq(&em_prime)) {
+            Ok(())
+        } else {
+            Err(invalid_signature())
+        }
+    }
+}
+
+/// [EMSA-PKCS1-v1_5-ENCODE](https://www.rfc-editor.org/rfc/rfc8017#section-9.2)
+fn emsa_pkcs1v15_encode(
+    hash: HashType,
+    message: &[u8],
+    em_len: usize,
+) -> Result<Vec<u8>, RsaError> {
+    let t = digest_info_der_serialize(hash, &hash.digest(message));
+
+    if em_len < t.len() + 11 {
+        return Err(RsaError::new(
+            RsaErrorKind::CryptographyError,
+            String::from("intended encoded message length too short"),
+        ));
+    }
+
+    // EM = 0x00 || 0x01 || PS || 0x00 || T, where PS is all 0xff
+    let mut em = Vec::with_capacity(em_len);
+    em.extend([0x00, 0x01]);
+    em.resize(em_len - t.len() - 1, 0xFF);
+    em.push(0x00);
+    em.extend(t);
+
+    Ok(em)
+}
+
+/// [EMSA-PSS-ENCODE](https://www.rfc-editor.org/rfc/rfc8017#section-9.1.1), starting from
+/// the hashed message `m_hash` and a `salt` of the desired length.
+fn emsa_pss_encode(
+    hash: HashType,
+    mgf: &dyn MaskGenerationFunction,
+    m_hash: &[u8],
+    salt: &[u8],
+    em_bits: usize,
+) -> Result<Vec<u8>, RsaError> {
+    let h_len = hash.output_len();
+    let em_len = em_bits.div_ceil(8);
+
+    if em_len < h_len + salt.len() + 2 {
+        return Err(RsaError::new(
+            RsaErrorKind::CryptographyError,
+            String::from("encoding error"),
+        ));
+    }
+
+    // H = Hash(M'), where M' = (0x)00 00 00 00 00 00 00 00 || mHash || salt
+    let h = pss_hash(hash, m_hash, salt);
+
+    // DB = PS || 0x01 || salt
+    let mut db = vec![0u8; em_len - salt.len() - h_len - 2];
+    db.push(0x01);
+    db.extend_from_slice(salt);
+
+    let db_mask = mgf.generate_mask(&h, em_len - h_len - 1)?;
+    xor_in_place(&mut db, &db_mask);
+    db[0] &= 0xFF >> (8 * em_len - em_bits);
+
+    // EM = maskedDB || H || 0xbc
+    let mut em = db;
+    em.extend(h);
+    em.push(0xBC);
+
+    Ok(em)
+}
+
+/// [EMSA-PSS-VERIFY](https://www.rfc-editor.org/rfc/rfc8017#section-9.1.2), starting from
+/// the hashed message `m_hash`. Returns whether the encoding is consistent.
+///
+/// If `s_len` is `None`, the salt length is recovered from the encoded message.
+fn emsa_pss_verify(
+    hash: HashType,
+    mgf: &dyn MaskGenerationFunction,
+    s_len: Option<usize>,
+    m_hash: &[u8],
+    em: &[u8],
+    em_bits: usize,
+) -> bool {
+    let h_len = hash.output_len();
+    let em_len = em.len();
+
+    if em_len < h_len + s_len.unwrap_or(0) + 2 || em[em_len - 1] != 0xBC {
+        return false;
+    }
+
+    let (masked_db, h) = em[..em_len - 1].split_at(em_len - h_len - 1);
+
+    let top_mask = 0xFF >> (8 * em_len - em_bits);
+    if masked_db[0] & !top_mask != 0 {
+        return false;
+    }
+
+    let Ok(db_mask) = mgf.generate_mask(h, em_len - h_len - 1) else {
+        return false;
+    };
+    let mut db = masked_db.to_vec();
+    xor_in_place(&mut db, &db_mask);
+    db[0] &= top_mask;
+
+    // DB = PS || 0x01 || salt, where PS is all zero
+    let separator = match s_len {
+        Some(s_len) => em_len - h_len - s_len - 2,
+        None => match db.iter().position(|b| *b != 0x00) {
+            Some(i) => i,
+            None => return false,
+        },
+    };
+
+    if db[..separator].iter().any(|b| *b != 0x00) || db[separator] != 0x01 {
+        return false;
+    }
+
+    let h_prime = pss_hash(hash, m_hash, &db[separator + 1..]);
+
+    h.ct_eq(&h_prime).into()
+}
+
+fn pss_hash(hash: HashType, m_hash: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut m_prime = vec![0u8; 8];
+    m_prime.extend_from_slice(m_hash);
+    m_prime.extend_from_slice(salt);
+
+    hash.digest(&m_prime)
+}
+
+fn invalid_signature() -> RsaError {
+    RsaError::new(
+        RsaErrorKind::CryptographyError,
+        String::from("invalid signature"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mask::Shake256Mgf;
+    use crate::serial::rsa_private_key_der_deserialize;
+    use crate::util::from_hex;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn pss_vector_key() -> (RsaPublicKey, RsaPrivateKey) {
+        let sk = rsa_private_key_der_deserialize(from_hex(PSS_KEY_DER)).unwrap();
+        let pk = RsaPublicKey::new(sk.public_exponent.clone(), sk.modulus.clone());
+
+        (pk, sk)
+    }
+
+    // From SigGenPSS_186-3.txt of the NIST CAVP FIPS 186-3 RSA test vectors:
+    // 3072-bit key, SHA-256 with MGF1-SHA-256, 32-octet salt.
+    const PSS_KEY_DER: &str = "
+        308206e40201000282018100a7a1882a7fb896786034d07fb1b9f6327c27bdd7ce6fe39c285ae3b6c34259adc0dc4f7b
+        9c7dec3ca4a20d3407339eedd7a12a421da18f5954673cac2ff059156ecc73c6861ec761e6a0f2a5a033a6768c6a42d8
+        b459e1b4932349e84efd92df59b45935f3d0e30817c66201aa99d07ae36c5d74f408d69cc08f044151ff4960e531360c
+        b19077833adf7bce77ecfaa133c0ccc63c93b856814569e0b9884ee554061b9a20ab46c38263c094dae791aa61a17f8d
+        16f0e85b7e5ce3b067ece89e20bc4e8f1ae814b276d234e04f4e766f501da74ea7e3817c24ea35d016676cece652b823
+        b051625573ca92757fc720d254ecf1dcbbfd21d98307561ecaab545480c7c52ad7e9fa6b597f5fe550559c2fe923205a
+        c1761a99737ca02d7b19822e008a8969349c87fb874c81620e38f613c8521f0381fe5ba55b74827dad3e1cf2aa29c693
+        3629f2b286ad11be88fa6436e7e3f64a75e3595290dc0d1cd5eee7aaac54959cc53bd5a934a365e72dd81a2bd4fb9a67
+        821bffedf2ef2bd94913de8b02031415a702820180073a5fc4cd642f6113dffc4f84035cee3a2b8acc549703751a1d6a
+        5eaa13487229a58ef7d7a522bb9f4f25510f1aa0f74c6a8fc8a5c5be8b91a674ede50e92f7e34a90a3c9da999fffb1d6
+        95e4588f451256c163484c151350cb9c7825a7d910845ee5cf826fecf9a7c0fbbbba22bb4a531c131d2e7761ba898f00
+        2ebef8ab87218511f81d3266e1ec07a7ca8622514c6dfdc86c67679a2c8f5f031de9a0c22b5a88060b46ee0c64d3b9af
+        3c0a379bcd9c6a1b51cf6480456d3fd6def94cd2a6c171dd3f010e3c9d662bc857208248c94ebcb9fd997b9ff4a7e5fd
+        95558569906525e741d78344f6f6cfdbd59d4faa52ee3fa964fb7cccb2d6be1935d211fe1498217716273939a946081f
+        d8509913fd47747c5c2f03efd4d6fc9c6fcfd8402e9f40a0a5b3de3ca2b3c0fac9456938faa6cf2c20e3912e5981c987
+        6d8ca1ff29b87a15eeae0ccce3f8a8f1e405091c083b98bcc5fe0d0deaae33c67c0394437f0eccb385b7efb17aeebba8
+        afaecca30a2f63eac8f0ac8f1eacad85bbcaf3960b0281c100dd553696db8ccb107609b8917e688bdd8373a8926bc9d1
+        14c1c77f7958070e236ca1bd2025ded59a71093b63afbfce39e92bde9ffca983959e7c3e18d75650612258c24eebb61a
+        1b4a68603a2721e3e2483d6da27475a228b1341c78f140948b5c922822ccaed76dae338dddec1e4c5c34b9c53f34a09f
+        f0b2b61a62254e73e6f0ac8013edc2cfa7ecbeb86fcc7309cb0f5b5eddb707af4b9337d34d672af413f3b6efd11e3b49
+        c978f06a356f6f4e0ea50a90797fe32ccaa983547ff18ea1670281c100c1e3089e1bea1141638ca912da01c134f67231
+        a2f737d97e28486e004a43e9c5592ff968ee18109fc71aa4c1a97aa88ece5c4734352bc0c1f67726bc4aac59c19301f2
+        3a705be5b3f7825fb284e58a950d795f63d18fe72231eaba9d6a5f90866f8dd34b2b0dfc132db8348efa5a62634e5584
+        a788aebbf073ccb4f3e9f5cde8d0c2e831412485c7f8cf1473abffabcc5d51d8a2a87a22f39d1a250b3cb66d90c57366
+        9071aeba9b1080dc079243094a9ae0e5a62e4e8b653cb57f54f4eeaf3d0281c05f8d638d1e0c47794d9305929b4b8728
+        1b69397f51b0809573edeb3dcb82f2376ee419491adfa4a9d7f6db51d9573f50d025cc64030744c05794d3350a8bd45b
+        3c5a69b8552e6cbfa2038fb33d4094dd8bb1095c36d4356a88c6ba114cad0c5acfdd224b3f4bc1af30f9a1ee5ea5407a
+        8ce3c71dd09a1ea33f44e63cb9c0cae176515e1e323874adc5c2b788e23bae5123a90b964e44ec2e9be90b496bb53796
+        7d05715e3737cd4c35f2bc87ab7cc9ee70116ed2528b3d62237413dfc56e0e470281c1008ced58a9828526335c9517b5
+        ac58c5ae7e74c9c36a7e6c7f571878f3319f52cb8eeaeeee64694ee4a49e582c53b77adac0fb6c808830560c4f130d92
+        5e7cbbcaa1221c8a4d54ce116b4c4ce52a56882c5c38000c441673cb91d381a3c5b09cd8ad3d74841fb8d7f9d5cebc4b
+        fd273d2fe28a08fc2df6a57207bef71c6a9d63d54a0d7677d450c6a6d4e9a937a82be8cd4a1677e7ac04712949c7683f
+        412862d40aeaa899640503aabc8794a5ff13e7c72df049ee0fb8203a99fe94a5003c225b0281c10087d010acad9e1540
+        cbfb2c03dcd987d3c2fbd0fa2cd10f2e5b216fad7016a58a98fb43fcaee266b21c99d213089696a0faea4710f8ee7bd9
+        a5eb2452341e58feec965d76e2133236f8b44e8bdd86a591acf8af501cdcc1a5cb1065793b09c0a5536ed414cca22163
+        758b0b41add238e62ea07acba89144f97b9e1509584210aced89986fead9b3d22253f0a7a7372e269a01356070d2e1af
+        ac08da15cf23ad29438b0b3fc8d9e8e9f99c8ca970ae703cca71974335adb96a16e85c97689c3a8c";
+    const PSS_MESSAGE: &str = "
+        c16499110ed577202aed2d3e4d51ded6c66373faef6533a860e1934c63484f87a8d9b92f3ac45197b2909710abba1daf
+        759fe0510e9bd8dd4d73cec961f06ee07acd9d42c6d40dac9f430ef90374a7e944bde5220096737454f96b614d0f6cdd
+        9f08ed529a4ad0e759cf3a023dc8a30b9a872974af9b2af6dc3d111d0feb7006";
+    const PSS_SALT: &str = "
+        3e07ade72a3f52530f53135a5d7d93217435ba001ea55a8f5d5d1304684874bc";
+    const PSS_SIGNATURE: &str = "
+        4335707da735cfd10411c9c048ca9b60bb46e2fe361e51fbe336f9508dc945afe075503d24f836610f2178996b52c411
+        693052d5d7aed97654a40074ed20ed6689c0501b7fbac21dc46b665ac079760086414406cd66f8537d1ebf0dce4cf0c9
+        8d4c30c71da359e9cd401ff49718fdd4d0f99efe70ad8dd8ba1304cefb88f24b0eedf70116da15932c76f0069551a245
+        b5fc3b91ec101f1d63b9853b598c6fa1c1acdbacf9626356c760119be0955644301896d9d0d3ea5e6443cb72ca29f4d4
+        5246d16d74d00568c219182feb191179e4593dc152c608fd80536329a533b3a631566814cd654f587c2d8ce696085e6e
+        d1b0b0278e60a049ec7a399f94fccae6462371a69695ef525e00936fa7d9781f9ee289d4105ee827a27996583033cedb
+        2f297e7b4926d906ce0d09d84128406ab33d7da0f8a1d4d2f666568686c394d139b0e5e99337758de85910a5fa25ca2a
+        a6d8fb1c777244e7d98de4c79bbd426a5e6f657e37477e01247432f83797fbf31b50d02b83f69ded26d4945b2bc3f86e";
+
+    #[test]
+    fn test_pss_sign_vector() {
+        let (_, sk) = pss_vector_key();
+        let params = PssParams::default();
+        let em_bits = sk.modulus.bits() as usize - 1;
+
+        let m_hash = params.hash.digest(&from_hex(PSS_MESSAGE));
+        let em = emsa_pss_encode(
+            params.hash,
+            &params.mgf,
+            &m_hash,
+            &from_hex(PSS_SALT),
+            em_bits,
+        )
+        .unwrap();
+
+        assert_eq!(sk.crypt_with_bytes(&em).unwrap(), from_hex(PSS_SIGNATURE));
+    }
+
+    #[test]
+    fn test_pss_verify_vector() {
+        let (pk, _) = pss_vector_key();
+        let message = from_hex(PSS_MESSAGE);
+        let signature = from_hex(PSS_SIGNATURE);
+
+        let params = PssParams::default();
+        assert!(pk.verify_pss(&params, &message, &signature).is_ok());
+
+        let params = PssParams::new(HashType::Sha256, HashType::Sha256, SaltLength::Auto);
+        assert!(pk.verify_pss(&params, &message, &signature).is_ok());
+
+        let params = PssParams::new(HashType::Sha256, HashType::Sha256, SaltLength::Fixed(20));
+        assert!(pk.verify_pss(&params, &message, &signature).is_err());
+
+        let mut tampered = signature.clone();
+        tampered[10] ^= 0x01;
+        assert!(pk
+            .verify_pss(&PssParams::default(), &message, &tampered)
+            .is_err());
+        assert!(pk
+            .verify_pss(&PssParams::default(), b"another message", &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_pss_roundtrip() {
+        let (pk, sk) = pss_vector_key();
+        let mut rng = StdRng::seed_from_u64(0);
+        let message = b"attack at dawn";
+
+        for salt_len in [SaltLength::Fixed(0), SaltLength::Digest, SaltLength::Auto] {
+            let params = PssParams::new(HashType::Sha1, HashType::Sha256, salt_len);
+
+            let signature = sk.sign_pss(&mut rng, &params, message).unwrap();
+            assert_eq!(signature.len(), sk.modulus_len());
+
+            assert!(pk.verify_pss(&params, message, &signature).is_ok());
+
+            let auto = PssParams::new(HashType::Sha1, HashType::Sha256, SaltLength::Auto);
+            assert!(pk.verify_pss(&auto, message, &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_pss_custom_mgf() {
+        let (pk, sk) = pss_vector_key();
+        let mut rng = StdRng::seed_from_u64(0);
+        let message = b"attack at dawn";
+
+        let params = PssParams::with_mgf(HashType::Sha256, Shake256Mgf, SaltLength::Digest);
+        let signature = sk.sign_pss(&mut rng, &params, message).unwrap();
+        assert!(pk.verify_pss(&params, message, &signature).is_ok());
+
+        assert!(pk
+            .verify_pss(&PssParams::default(), message, &signature)
+            .is_err());
+    }
+
+    // From SigGen15_186-3.txt of the NIST CAVP FIPS 186-3 RSA test vectors:
+    // 2048-bit key, signing with SHA-256 and SHA-512.
+    const PKCS1V15_KEY_DER: &str = "
+        308204a40201000282010100cea80475324c1dc8347827818da58bac069d3419c614a6ea1ac6a3b510dcd72cc5169549
+        05e9fef908d45e13006adf27d467a7d83c111d1a5df15ef293771aefb920032a5bb989f8e4f5e1b05093d3f130f984c0
+        7a772a3683f4dc6fb28a96815b32123ccdd13954f19d5b8b24a103e771a34c328755c65ed64e1924ffd04d30b2142cc2
+        62f6e0048fef6dbc652f21479ea1c4b1d66d28f4d46ef7185e390cbfa2e02380582f3188bb94ebbf05d31487a09aff01
+        fcbb4cd4bfd1f0a833b38c11813c84360bb53c7d4481031c40bad8713bb6b835cb08098ed15ba31ee4ba728a8c8e10f7
+        294e1b4163b7aee57277bfd881a6f9d43e02c6925aa3a043fb7fb78d0203260445028201000997634c477c1a039d44c8
+        10b2aaa3c7862b0b88d3708272e1e15f66fc9389709f8a11f3ea6a5af7effa2d01c189c50f0d5bcbe3fa272e56cfc4a4
+        e1d388a9dcd65df8628902556c8b6bb6a641709b5a35dd2622c73d4640bfa1359d0e76e1f219f8e33eb9bd0b59ec198e
+        b2fccaae0346bd8b401e12e3c67cb629569c185a2e0f35a2f741644c1cca5ebb139d77a89a2953fc5e30048c0e619f07
+        c8d21d1e56b8af07193d0fdf3f49cd49f2ef3138b5138862f1470bd2d16e34a2b9e7777a6c8c8d4cb94b4e8b5d616cd5
+        393753e7b0f31cc7da559ba8e98d888914e334773baf498ad88d9631eb5fe32e53a4145bf0ba548bf2b0a50c63f67b14
+        e398a34b0d02818100f364e16ef12017ec95b192308c01e087cee619ab50a5d537cc01841dc92b30bcef0d9f2c6bbd5d
+        c10bdf5b9f6c354a4f9f210520caa72b4f5c36b8d33f10324c55956141891e45b84b49f59ea5bfac6ffa38900aca5099
+        afcd02f6a8257c41ce5bb2e4153832b5c22f91eb389fa2035c3cf9b3374531c483cb30ceb007259b1d02818100d95c09
+        95fabdfcbccfe63e0f3262f806869ab571e1793e97234cbb9bd4b6872a7695389955cf6ce7245345a5df8021f7d95195
+        63afbc2667f5311fad093de2c02cd069109b630d68e3bf767f8a788a6add7ab199f2d8f6a40b7c1910d9dab52ac80d0d
+        333aacab321a9309dc884ddd4db637a0c1115ae3c08efa683f99eb733102818100d4f7ef9f9be947ba9d1b3bce59e560
+        8839a1e464553e1b6d113d0f636758bbb473a89f9949836ead40b6f314eee3ac2244d7b6f379e83f30e17783ad68d508
+        6897889c051c26e1558a4a220bfc242995860644b5d7a3ef513ac612b9c6c0a2021bb6b9cde7dbd21fe5858746c79563
+        e9bab7d06b43aab43a0a5cafab4519a6610281803db2386f174f2ea3ef4b6bd1601749ce2d6afa8be35f051178621f16
+        a23ad36eba03c073136389241969e5b87edb0fcbcf1a0bd6e1aee97bae1f2d97aabe19b17dbe7d9492cdb68a0897f572
+        350e846c669660dc978c5068da598524fca8a136358d3e5f8f6ad5cf78d9089c93f473189162ce0f8c4902a19902b633
+        b3e6926d02818100ddc971183dcf3450c43e06ba2af32379eedeb2d678513fb706b75a006098154041f4b09e6be385d4
+        b25d80ec241c899e4a986a17b0a121daab91a1e4fc5a1802a7074df3fb3f7661f0e1c97799e36d21de937cc4209585db
+        30a56af0a228e001036ed792625e5368ce101574a2e9767f07338949f0afdf358cecd18c6d6f3f55";
+    const PKCS1V15_SHA256_MESSAGE: &str = "
+        5af283b1b76ab2a695d794c23b35ca7371fc779e92ebf589e304c7f923d8cf976304c19818fcd89d6f07c8d8e08bf371
+        068bdf28ae6ee83b2e02328af8c0e2f96e528e16f852f1fc5455e4772e288a68f159ca6bdcf902b858a1f94789b31638
+        23e2d0717ff56689eec7d0e54d93f520d96e1eb04515abc70ae90578ff38d31b";
+    const PKCS1V15_SHA256_SIGNATURE: &str = "
+        6b8be97d9e518a2ede746ff4a7d91a84a1fc665b52f154a927650db6e7348c69f8c8881f7bcf9b1a6d3366eed30c3aed
+        4e93c203c43f5528a45de791895747ade9c5fa5eee81427edee02082147aa311712a6ad5fb1732e93b3d6cd23ffd46a0
+        b3caf62a8b69957cc68ae39f9993c1a779599cdda949bdaababb77f248fcfeaa44059be5459fb9b899278e929528ee13
+        0facd53372ecbc42f3e8de2998425860406440f248d817432de687112e504d734028e6c5620fa282ca07647006cf0a2f
+        f83e19a916554cc61810c2e855305db4e5cf893a6a96767365794556ff033359084d7e38a8456e68e21155b76151314a
+        29875feee09557161cbc654541e89e42";
+    const PKCS1V15_SHA512_MESSAGE: &str = "
+        a7c309d44a57188bbd7b726b98b98ce12582228e1415864870a23961d2afb82cd5bc98bec922d5f2ac4168b056da176e
+        f3ba91f6b699ba6acc4144868ff37f26fd06720868d12ad26ecb52572cf10416af68df03ab645a8b704857d2190ffc3f
+        07eabe3a8e2abe34ed6159e884c4fae141d4333d5c3e0db044ff9cccd9cbd67f";
+    const PKCS1V15_SHA512_SIGNATURE: &str = "
+        148af61ed5ea8a87a08b3f403929bf8031db4fd3999b64409ba489f97a3ee5208ea4202d2ec18734f615003a51f77441
+        085be6ac0f11810ffa2dad58f0e186d5520ac2b8a5d3966e8d2abb8074e13b50a4e7de83be10a66fdc7ca18118c5774f
+        781212de9efebc6376fcdddc65a3b1b8f1ab31492fe478259ce719b3db587498d879a01dec96e8eabeb07ff7073f3f3e
+        b446084955ca26329a791315a2c259d225e26b2154b2047b21faba68115bfd962e5e24ec52d7c5d231e3044cbcd8c880
+        4855703cbaa622b15b6ef78c7421a367166f1b02576c87360593da75b7189efafd1082bd59f6857f1701f646c24d70c9
+        5273c49d5b11e6afe258821b55c1680c";
+
+    // The DigestInfo prefixes listed in Note 1 of Section 9.2 of RFC8017.
+    #[test]
+    fn test_digest_info_prefixes() {
+        let prefixes = [
+            (
+                HashType::Sha1,
+                "30 21 30 09 06 05 2b 0e 03 02 1a 05 00 04 14",
+            ),
+            (
+                HashType::Sha224,
+                "30 2d 30 0d 06 09 60 86 48 01 65 03 04 02 04 05 00 04 1c",
+            ),
+            (
+                HashType::Sha256,
+                "30 31 30 0d 06 09 60 86 48 01 65 03 04 02 01 05 00 04 20",
+            ),
+            (
+                HashType::Sha384,
+                "30 41 30 0d 06 09 60 86 48 01 65 03 04 02 02 05 00 04 30",
+            ),
+            (
+                HashType::Sha512,
+                "30 51 30 0d 06 09 60 86 48 01 65 03 04 02 03 05 00 04 40",
+            ),
+            (
+                HashType::Sha512_256,
+                "30 31 30 0d 06 09 60 86 48 01 65 03 04 02 06 05 00 04 20",
+            ),
+        ];
+
+        for (hash, prefix) in prefixes {
+            let digest = hash.digest(b"abc");
+            let mut expected = from_hex(prefix);
+            expected.extend(&digest);
+
+            assert_eq!(digest_info_der_serialize(hash, &digest), expected);
+        }
+    }
+
+    #[test]
+    fn test_pkcs1v15_sign_vectors() {
+        let sk = rsa_private_key_der_deserialize(from_hex(PKCS1V15_KEY_DER)).unwrap();
+
+        let s = sk
+            .sign_pkcs1v15(HashType::Sha256, from_hex(PKCS1V15_SHA256_MESSAGE))
+            .unwrap();
+        assert_eq!(s, from_hex(PKCS1V15_SHA256_SIGNATURE));
+
+        let s = sk
+            .sign_pkcs1v15(HashType::Sha512, from_hex(PKCS1V15_SHA512_MESSAGE))
+            .unwrap();
+        assert_eq!(s, from_hex(PKCS1V15_SHA512_SIGNATURE));
+    }
+
+    #[test]
+    fn test_pkcs1v15_verify_vectors() {
+        let sk = rsa_private_key_der_deserialize(from_hex(PKCS1V15_KEY_DER)).unwrap();
+        let pk = RsaPublicKey::new(sk.public_exponent.clone(), sk.modulus.clone());
+
+        let message = from_hex(PKCS1V15_SHA256_MESSAGE);
+        let signature = from_hex(PKCS1V15_SHA256_SIGNATURE);
+
+        assert!(pk
+            .verify_pkcs1v15(HashType::Sha256, &message, &signature)
+            .is_ok());
+        assert!(pk
+            .verify_pkcs1v15(HashType::Sha512_256, &message, &signature)
+            .is_err());
+        assert!(pk
+            .verify_pkcs1v15(HashType::Sha256, b"another message", &signature)
+            .is_err());
+
+        let mut tampered = signature.clone();
+        tampered[0] ^= 0x01;
+        assert!(pk
+            .verify_pkcs1v15(HashType::Sha256, &message, &tampered)
+            .is_err());
+    }
+}
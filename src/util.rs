@@ -1,39 +1,247 @@
 //! Utility functions
-use num::{BigUint, Integer, ToPrimitive};
-
-use crate::keygen::{RsaCsprng, RSA_PRIME_NUMBER_BIT_LENGTH};
+use num::{BigUint, Integer, One, ToPrimitive, Zero};
+
+#[cfg(feature = "parallel")]
+use rand::SeedableRng;
+#[cfg(feature = "parallel")]
+use rand_chacha::ChaCha20Rng;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "parallel")]
+use std::sync::mpsc;
+
+use crate::errors::{RsaError, RsaErrorKind};
+use crate::keygen::{RsaCsprng, RSA_MIN_PRIME_BIT_LENGTH};
 use crate::static_init::{PRECOMPUTED_PRIMES, PRECOMPUTED_PRIMES_LEN};
 
-/// Generates a candidate prime (see `keygen.rs` for bit length) by repeated random drawing.
+/// [I2OSP](https://www.rfc-editor.org/rfc/rfc8017#section-4.1)
+///
+/// Converts a nonnegative integer to a big-endian octet string of exactly `x_len` octets.
+/// Fails with an `integer too large` error if `x >= 256^x_len`.
+pub fn i2osp(x: &BigUint, x_len: usize) -> Result<Vec<u8>, RsaError> {
+    if *x == BigUint::ZERO {
+        return Ok(vec![0u8; x_len]);
+    }
+
+    let bytes = x.to_bytes_be();
+
+    if bytes.len() > x_len {
+        return Err(RsaError::new(
+            RsaErrorKind::CryptographyError,
+            String::from("integer too large"),
+        ));
+    }
+
+    let mut ret = vec![0u8; x_len - bytes.len()];
+    ret.extend(bytes);
+
+    Ok(ret)
+}
+
+/// [OS2IP](https://www.rfc-editor.org/rfc/rfc8017#section-4.2)
+///
+/// Converts a big-endian octet string to a nonnegative integer.
+pub fn os2ip(x: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(x)
+}
+
+/// Generates a candidate prime of exactly `bits` bits by repeated random drawing.
 /// Applies the Miller-Rabin Primality test `mr_iterations` times to test for primality.
 ///
 /// `rng`: The CSPRNG used to generate primes
 ///
 /// `mr_iterations`: The number of miller-rabin primality test iterations to conduct, default 1.
-pub fn generate_candidate_prime(rng: &mut Box<dyn RsaCsprng>, mr_iterations: usize) -> BigUint {
-    probable_prime(rng, mr_iterations)
+///
+/// `bits`: The bit length of the prime, half the bit length of the RSA modulus.
+/// Must be at least `RSA_MIN_PRIME_BIT_LENGTH`.
+pub fn generate_candidate_prime<R: RsaCsprng>(
+    rng: &mut R,
+    mr_iterations: usize,
+    bits: u64,
+) -> Result<BigUint, RsaError> {
+    generate_prime_with_test(
+        rng,
+        &MillerRabin::new(mr_iterations),
+        bits,
+        SieveSize::default(),
+    )
+}
+
+/// Overwrites the digits of `x` with zeros, and sets it to zero.
+///
+/// `BigUint` doesn't expose its buffer, so the zeros are written through `assign_from_slice`,
+/// with a nonzero top digit that stops the buffer from being truncated or reallocated.
+/// Spare capacity beyond the current digits, and copies left behind by earlier arithmetic,
+/// can't be reached and aren't wiped.
+pub(crate) fn zeroize_biguint(x: &mut BigUint) {
+    let len = x.iter_u32_digits().len();
+    if len == 0 {
+        return;
+    }
+
+    let mut digits = vec![0u32; len];
+    digits[len - 1] = 1;
+    x.assign_from_slice(&digits);
+
+    // keep the zeros from being optimized away as dead stores
+    std::hint::black_box(&*x);
+    x.set_zero();
+}
+
+/// Generates a candidate prime of exactly `bits` bits by repeated random drawing,
+/// using the given `PrimalityTest` to test for primality.
+///
+/// `rng`: The CSPRNG used to generate primes
+///
+/// `primality_test`: The test every candidate surviving the sieve must pass.
+///
+/// `bits`: The bit length of the prime. Must be at least `RSA_MIN_PRIME_BIT_LENGTH`.
+///
+/// `sieve_size`: The number of small primes candidates are sieved with.
+pub fn generate_prime_with_test<R: RsaCsprng>(
+    rng: &mut R,
+    primality_test: &dyn PrimalityTest,
+    bits: u64,
+    sieve_size: SieveSize,
+) -> Result<BigUint, RsaError> {
+    check_prime_bits(bits)?;
+
+    Ok(probable_prime(rng, primality_test, bits, sieve_size, None).unwrap())
+}
+
+/// Generates a candidate prime of exactly `bits` bits, searching on `threads` worker threads.
+/// Returns the first prime found by any of the workers.
+///
+/// Each worker draws its candidates from its own ChaCha20 stream, seeded from `rng`.
+/// Since the workers race each other, the result isn't reproducible, even with a seeded `rng`.
+///
+/// Like `generate_prime_with_test`, `bits` must be at least `RSA_MIN_PRIME_BIT_LENGTH`.
+#[cfg(feature = "parallel")]
+pub fn generate_prime_parallel<R: RsaCsprng>(
+    rng: &mut R,
+    primality_test: &dyn PrimalityTest,
+    bits: u64,
+    sieve_size: SieveSize,
+    threads: usize,
+) -> Result<BigUint, RsaError> {
+    check_prime_bits(bits)?;
+
+    let seeds: Vec<[u8; 32]> = (0..threads.max(1))
+        .map(|_| {
+            let mut seed = [0u8; 32];
+            rng.fill_bytes(&mut seed);
+            seed
+        })
+        .collect();
+
+    let found = AtomicBool::new(false);
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        for seed in seeds {
+            let found = &found;
+            let tx = tx.clone();
+
+            scope.spawn(move || {
+                let mut rng = ChaCha20Rng::from_seed(seed);
+
+                if let Some(prime) =
+                    probable_prime(&mut rng, primality_test, bits, sieve_size, Some(found))
+                {
+                    found.store(true, Ordering::Relaxed);
+                    let _ = tx.send(prime);
+                }
+            });
+        }
+    });
+
+    // every worker has finished, and at least one found a prime
+    Ok(rx.recv().unwrap())
+}
+
+/// Fails for primes shorter than `RSA_MIN_PRIME_BIT_LENGTH`. Below 2 bits there's no
+/// candidate to draw, and small enough candidates all fall in the sieve, so the search
+/// would never end.
+fn check_prime_bits(bits: u64) -> Result<(), RsaError> {
+    if bits < RSA_MIN_PRIME_BIT_LENGTH {
+        return Err(RsaError::new(
+            RsaErrorKind::OptionsError,
+            format!(
+                "primes must be at least {} bits long, got {}.",
+                RSA_MIN_PRIME_BIT_LENGTH, bits
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The number of small primes that candidates are sieved with during prime generation.
+///
+/// A larger sieve discards more composites before the much slower primality test,
+/// at the cost of computing more remainders for every candidate.
+/// The primes are generated at build time, see `build.rs`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SieveSize {
+    Primes256,
+    /// The size used by OpenSSL for moduli of 2048 bits or more.
+    #[default]
+    Primes512,
+    Primes1024,
+    Primes2048,
+    Primes4096,
+}
+
+impl SieveSize {
+    /// The number of primes in the sieve.
+    pub fn size(&self) -> usize {
+        match self {
+            SieveSize::Primes256 => 256,
+            SieveSize::Primes512 => 512,
+            SieveSize::Primes1024 => 1024,
+            SieveSize::Primes2048 => 2048,
+            SieveSize::Primes4096 => PRECOMPUTED_PRIMES_LEN,
+        }
+    }
+
+    /// The primes in the sieve, starting from 2.
+    pub fn primes(&self) -> &'static [u64] {
+        &PRECOMPUTED_PRIMES[..self.size()]
+    }
 }
 
 /// Replicating the probable_prime() generation from OpenSSL
 /// [See Source](https://github.com/openssl/openssl/blob/4a4505cc645d2e862e368e2823e921a564112ca2/crypto/bn/bn_prime.c#L487)
-fn probable_prime(rng: &mut Box<dyn RsaCsprng>, mr_iterations: usize) -> BigUint {
-    let mut mods: [u64; PRECOMPUTED_PRIMES_LEN] = [0; 512];
-    const MAX_DELTA: u64 = u64::MAX - PRECOMPUTED_PRIMES[PRECOMPUTED_PRIMES_LEN - 1];
+///
+/// Gives up and returns `None` once `stop` is set.
+fn probable_prime<R: RsaCsprng>(
+    rng: &mut R,
+    primality_test: &dyn PrimalityTest,
+    bits: u64,
+    sieve_size: SieveSize,
+    stop: Option<&AtomicBool>,
+) -> Option<BigUint> {
+    let primes = sieve_size.primes();
+    let mut mods: Vec<u64> = vec![0; primes.len()];
+    let max_delta: u64 = u64::MAX - primes[primes.len() - 1];
 
     'full_gen: loop {
-        let mut candidate = generate_random_odd_big_uint(rng);
+        if stop.is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+            return None;
+        }
+
+        let mut candidate = generate_random_odd_big_uint(rng, bits);
 
-        for i in 1..PRECOMPUTED_PRIMES_LEN {
-            mods[i] = (&candidate % PRECOMPUTED_PRIMES[i]).to_u64().unwrap();
+        for i in 1..primes.len() {
+            mods[i] = (&candidate % primes[i]).to_u64().unwrap();
         }
 
         let mut delta: u64 = 0;
 
         'check_mods: loop {
-            for i in 1..PRECOMPUTED_PRIMES_LEN {
-                if (mods[i] + delta) % PRECOMPUTED_PRIMES[i] == 0 {
+            for i in 1..primes.len() {
+                if (mods[i] + delta).is_multiple_of(primes[i]) {
                     delta += 2;
-                    if delta > MAX_DELTA {
+                    if delta > max_delta {
                         continue 'full_gen;
                     }
                     continue 'check_mods;
@@ -43,30 +251,31 @@ fn probable_prime(rng: &mut Box<dyn RsaCsprng>, mr_iterations: usize) -> BigUint
         }
 
         candidate += delta;
-        if candidate.bits() != RSA_PRIME_NUMBER_BIT_LENGTH {
+        if candidate.bits() != bits {
             continue;
         }
 
-        if !miller_rabin_is_prime(rng, &candidate, mr_iterations) {
+        if !primality_test.is_prime(rng, &candidate) {
             continue;
         }
 
-        return candidate;
+        return Some(candidate);
     }
 }
 
-/// Generates a large, odd integer.
-/// Top 2 bits are always set
-fn generate_random_odd_big_uint(rng: &mut Box<dyn RsaCsprng>) -> BigUint {
-    let mut x = rng.gen_biguint(RSA_PRIME_NUMBER_BIT_LENGTH);
+/// Generates a large, odd integer of exactly `bits >= 2` bits.
+/// Top 2 bits are always set, so that the product of two such integers
+/// is exactly `2 * bits` bits long.
+fn generate_random_odd_big_uint<R: RsaCsprng + ?Sized>(rng: &mut R, bits: u64) -> BigUint {
+    // drawn as big-endian bytes rather than with `gen_biguint`, so that the candidates
+    // drawn from a given rng don't depend on the target's digit size
+    let mut bytes = vec![0u8; bits.div_ceil(8) as usize];
+    rng.fill_bytes(&mut bytes);
+    let mut x = BigUint::from_bytes_be(&bytes) >> (bytes.len() as u64 * 8 - bits);
 
-    if x.is_even() {
-        x.dec()
-    }
-
-    let num_bits = x.bits();
-    x.set_bit(num_bits, true);
-    x.set_bit(num_bits - 1, true);
+    x.set_bit(bits - 1, true);
+    x.set_bit(bits - 2, true);
+    x.set_bit(0, true);
 
     x
 }
@@ -76,8 +285,8 @@ fn generate_random_odd_big_uint(rng: &mut Box<dyn RsaCsprng>) -> BigUint {
 ///
 /// Deprecated. Functionality moved into `probable_prime()`.
 fn _small_prime_sieve(prime_candidate: &BigUint) -> bool {
-    for i in 1..PRECOMPUTED_PRIMES_LEN {
-        if prime_candidate % PRECOMPUTED_PRIMES[i] == BigUint::ZERO {
+    for prime in &PRECOMPUTED_PRIMES[1..] {
+        if prime_candidate % prime == BigUint::ZERO {
             return false;
         }
     }
@@ -91,119 +300,626 @@ pub fn carmichael_totient(p: &BigUint, q: &BigUint) -> BigUint {
     (p - 1u32).lcm(&(q - 1u32))
 }
 
+/// Computes the Carmichael totient of a multi-prime modulus `n = r_1 * r_2 * ... * r_u`,
+/// which is `lcm(r_1 - 1, r_2 - 1, ..., r_u - 1)`.
+pub fn carmichael_totient_multi_prime(primes: &[BigUint]) -> BigUint {
+    primes
+        .iter()
+        .fold(BigUint::ZERO + 1u32, |acc, r| acc.lcm(&(r - 1u32)))
+}
+
+/// A test deciding whether a candidate is prime, used during key generation.
+///
+/// Implement this to replace the built-in tests, e.g. with an audited implementation,
+/// and pass it to `KeyPairBuilder::with_primality_test`.
+pub trait PrimalityTest: Send + Sync {
+    /// Returns true if `prime_candidate` is (probably) prime. Probabilistic tests
+    /// draw their randomness from `rng`.
+    fn is_prime(&self, rng: &mut dyn RsaCsprng, prime_candidate: &BigUint) -> bool;
+}
+
+/// The Miller-Rabin Primality Test, with a fixed number of random bases.
+/// See `miller_rabin_is_prime`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MillerRabin {
+    pub iterations: usize,
+}
+
+impl MillerRabin {
+    pub fn new(iterations: usize) -> Self {
+        Self { iterations }
+    }
+}
+
+impl PrimalityTest for MillerRabin {
+    fn is_prime(&self, rng: &mut dyn RsaCsprng, prime_candidate: &BigUint) -> bool {
+        miller_rabin_is_prime(rng, prime_candidate, self.iterations)
+    }
+}
+
+/// The Baillie-PSW Primality Test. See `baillie_psw_is_prime`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BailliePsw;
+
+impl PrimalityTest for BailliePsw {
+    fn is_prime(&self, _rng: &mut dyn RsaCsprng, prime_candidate: &BigUint) -> bool {
+        baillie_psw_is_prime(prime_candidate)
+    }
+}
+
+/// The Miller-Rabin Primality Test, with at least as many random bases as
+/// [FIPS 186-5, Table B.1](https://csrc.nist.gov/pubs/fips/186-5/final)
+/// requires for the bit length of the candidate.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FipsMillerRabin {
+    /// Conducts more rounds than the table requires, if greater.
+    pub min_iterations: usize,
+}
+
+impl FipsMillerRabin {
+    pub fn new(min_iterations: usize) -> Self {
+        Self { min_iterations }
+    }
+}
+
+impl PrimalityTest for FipsMillerRabin {
+    fn is_prime(&self, rng: &mut dyn RsaCsprng, prime_candidate: &BigUint) -> bool {
+        let iterations = self
+            .min_iterations
+            .max(fips_miller_rabin_rounds(prime_candidate.bits()));
+
+        miller_rabin_is_prime(rng, prime_candidate, iterations)
+    }
+}
+
+/// The minimum number of Miller-Rabin rounds for generating an RSA prime of `prime_bits` bits,
+/// from the "M-R tests only" column of
+/// [FIPS 186-5, Table B.1](https://csrc.nist.gov/pubs/fips/186-5/final).
+pub fn fips_miller_rabin_rounds(prime_bits: u64) -> usize {
+    match prime_bits {
+        ..=1024 => 5,
+        _ => 4,
+    }
+}
+
+/// The first 12 primes. Using all of them as Miller-Rabin bases gives a deterministic
+/// answer for every candidate below 318665857834031151167461 (roughly 2^78),
+/// the smallest strong pseudoprime to all 12 bases.
+pub const DETERMINISTIC_MILLER_RABIN_BASES: [u32; 12] =
+    [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
 /// Miller-Rabin Primality Test. \
-/// A candidate prime p is an integer that we want to test for primality. \
-/// A successful candidate will be an odd integer. \
-/// Every odd prime can be decomposed into the form (p - 1) = 2^u * r,
-/// where r is an odd integer.
-pub fn miller_rabin_is_prime(
-    rng: &mut Box<dyn RsaCsprng>,
+/// A candidate prime n is an integer that we want to test for primality. \
+/// Every odd candidate can be decomposed into the form (n - 1) = 2^s * d,
+/// where d is an odd integer. \
+/// Conducts `iterations` rounds, each with a random base drawn from `[2, n - 2]`.
+/// A composite passes a single round with probability at most 1/4.
+pub fn miller_rabin_is_prime<R: RsaCsprng + ?Sized>(
+    rng: &mut R,
     prime_candidate: &BigUint,
     iterations: usize,
 ) -> bool {
+    if let Some(is_prime) = trivial_primality(prime_candidate) {
+        return is_prime;
+    }
+
     let two = BigUint::ZERO + 2u32;
-    let one = BigUint::ZERO + 1u32;
+    let n_minus_one = prime_candidate - 1u32;
 
-    if prime_candidate.eq(&BigUint::ZERO) {
-        return false;
-    }
+    (0..iterations).all(|_| {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        is_strong_probable_prime(prime_candidate, &a)
+    })
+}
 
-    // if the prime candidate is even or 2 then we don't want to count it.
-    if prime_candidate.eq(&two) || prime_candidate.modpow(&one, &two) == BigUint::ZERO {
-        return false;
+/// Miller-Rabin Primality Test over fixed `bases`. \
+/// With `DETERMINISTIC_MILLER_RABIN_BASES`, the result is exact for small candidates.
+pub fn miller_rabin_with_bases(prime_candidate: &BigUint, bases: &[u32]) -> bool {
+    if let Some(is_prime) = trivial_primality(prime_candidate) {
+        return is_prime;
     }
 
-    let p_minus_one = prime_candidate - 1u32;
+    let n_minus_one = prime_candidate - 1u32;
 
-    let u = p_minus_one.trailing_zeros().unwrap() as u32;
-    let r = &p_minus_one >> u;
+    bases.iter().all(|base| {
+        let a = BigUint::from(*base) % prime_candidate;
 
-    assert!(p_minus_one == (two.pow(u) * &r));
+        // bases that are 0, 1 or -1 mod n say nothing about n
+        a <= BigUint::one() || a == n_minus_one || is_strong_probable_prime(prime_candidate, &a)
+    })
+}
 
-    'outer: for _ in 0..iterations {
-        let a = rng.gen_biguint_range(&two, &p_minus_one);
+/// Strong Lucas Probable Prime Test, with parameters `P = 1, Q = (1 - D) / 4`,
+/// where `D` is the first of `5, -7, 9, -11, ...` with Jacobi symbol `(D/n) = -1`
+/// (Selfridge's Method A). \
+/// Writing `n + 1 = 2^s * d` with `d` odd, an odd prime `n` satisfies either `U_d = 0 mod n`,
+/// or `V_(d * 2^r) = 0 mod n` for some `0 <= r < s`.
+///
+/// [See source](https://en.wikipedia.org/wiki/Lucas_pseudoprime#Strong_Lucas_pseudoprimes)
+pub fn strong_lucas_is_prime(prime_candidate: &BigUint) -> bool {
+    if let Some(is_prime) = trivial_primality(prime_candidate) {
+        return is_prime;
+    }
 
-        let mut z = a.modpow(&r, &prime_candidate);
+    let n = prime_candidate;
 
-        if &z == &one {
-            continue;
+    // no suitable D exists for perfect squares
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return false;
+    }
+
+    let mut d_abs: u64 = 5;
+    let mut d_negative = false;
+    let d = loop {
+        let d_mod_n = BigUint::from(d_abs) % n;
+        let d = if d_negative && !d_mod_n.is_zero() {
+            n - d_mod_n
+        } else {
+            d_mod_n
+        };
+
+        match jacobi_symbol(&d, n) {
+            -1 => break d,
+            // D shares a factor with n
+            0 => return *n == BigUint::from(d_abs),
+            _ => {}
         }
 
-        for j in 0..u {
-            let exp = two.pow(j) * &r;
-            z = z.modpow(&exp, &prime_candidate);
+        d_abs += 2;
+        d_negative = !d_negative;
+    };
+
+    // Q = (1 - D) / 4, which is positive when D < 0
+    let q_abs = (if d_negative { d_abs + 1 } else { d_abs - 1 }) / 4;
+    let q_abs = BigUint::from(q_abs) % n;
+    let q = if d_negative || q_abs.is_zero() {
+        q_abs
+    } else {
+        n - q_abs
+    };
+
+    let half = |x: BigUint| {
+        if x.is_odd() {
+            (x + n) >> 1
+        } else {
+            x >> 1
+        }
+    };
+
+    let n_plus_one = n + 1u32;
+    let s = n_plus_one.trailing_zeros().unwrap();
+    let k = &n_plus_one >> s;
+
+    // U_1 = 1, V_1 = P = 1, Q^1 = Q
+    let mut u = BigUint::one();
+    let mut v = BigUint::one();
+    let mut q_k = q.clone();
+
+    for i in (0..k.bits() - 1).rev() {
+        // U_2k = U_k * V_k, V_2k = V_k^2 - 2Q^k
+        u = (&u * &v) % n;
+        v = (&v * &v + n - ((&q_k << 1u32) % n)) % n;
+        q_k = (&q_k * &q_k) % n;
+
+        if k.bit(i) {
+            // U_(2k+1) = (P * U_2k + V_2k) / 2, V_(2k+1) = (D * U_2k + P * V_2k) / 2
+            let u_next = half((&u + &v) % n);
+            v = half((&d * &u + &v) % n);
+            u = u_next;
+            q_k = (&q_k * &q) % n;
+        }
+    }
 
-            if z == p_minus_one {
-                continue 'outer;
-            }
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+
+    for _ in 1..s {
+        v = (&v * &v + n - ((&q_k << 1u32) % n)) % n;
+        if v.is_zero() {
+            return true;
         }
+        q_k = (&q_k * &q_k) % n;
+    }
 
-        return false;
+    false
+}
+
+/// [Baillie-PSW Primality Test](https://en.wikipedia.org/wiki/Baillie%E2%80%93PSW_primality_test) \
+/// Trial division by the primes of the default sieve, followed by a Miller-Rabin test to base 2,
+/// and a strong Lucas test. No composite is known to pass it.
+pub fn baillie_psw_is_prime(prime_candidate: &BigUint) -> bool {
+    if let Some(is_prime) = trivial_primality(prime_candidate) {
+        return is_prime;
     }
 
-    true
+    for prime in &SieveSize::default().primes()[1..] {
+        if *prime_candidate == BigUint::from(*prime) {
+            return true;
+        }
+        if (prime_candidate % prime).is_zero() {
+            return false;
+        }
+    }
+
+    miller_rabin_with_bases(prime_candidate, &[2]) && strong_lucas_is_prime(prime_candidate)
 }
 
-/// `a`: potential witness \
-/// `p`: prime candidate \
-/// `r, u`: values such that `p - 1` = `2^u * r` \
-/// Returns true if a is a valid witness, false otherwise
-///
-/// Deprecated. Functionality moved into `miller_rabin_is_prime.`
-fn _test_witness(a: &BigUint, p: &BigUint, u: &u32, r: &BigUint) -> bool {
-    let mut z: BigUint = a.modpow(r, p);
+/// Decides candidates below 4 and even candidates, which the tests above can't handle.
+fn trivial_primality(prime_candidate: &BigUint) -> Option<bool> {
+    if *prime_candidate < BigUint::from(4u32) {
+        Some(*prime_candidate >= BigUint::from(2u32))
+    } else if prime_candidate.is_even() {
+        Some(false)
+    } else {
+        None
+    }
+}
 
-    let two: BigUint = BigUint::ZERO + 2u32;
-    let p_minus_one: BigUint = p - 1u32;
+/// Checks whether odd `n > 3` is a strong probable prime to base `a`, where `1 < a < n - 1`.
+fn is_strong_probable_prime(n: &BigUint, a: &BigUint) -> bool {
+    let n_minus_one = n - 1u32;
 
-    if (&z - 1u32) == BigUint::ZERO {
-        return false;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+
+    let mut x = a.modpow(&d, n);
+    if x.is_one() || x == n_minus_one {
+        return true;
     }
 
-    for i in 0..*u {
-        let exp = two.pow(i) * r;
-        z = z.modpow(&exp, p);
+    for _ in 1..s {
+        x = (&x * &x) % n;
 
-        if z == p_minus_one {
+        if x == n_minus_one {
+            return true;
+        }
+        if x.is_one() {
             return false;
         }
     }
 
-    true
+    false
+}
+
+/// Computes the Jacobi symbol `(a/n)` for odd `n`.
+fn jacobi_symbol(a: &BigUint, n: &BigUint) -> i8 {
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+
+    while !a.is_zero() {
+        let twos = a.trailing_zeros().unwrap();
+        a >>= twos;
+
+        // (2/n) = -1 when n = 3, 5 mod 8
+        let n_mod_8 = (&n % 8u32).to_u8().unwrap();
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+
+        // quadratic reciprocity
+        std::mem::swap(&mut a, &mut n);
+        if (&a % 4u32) == BigUint::from(3u32) && (&n % 4u32) == BigUint::from(3u32) {
+            result = -result;
+        }
+
+        a %= &n;
+    }
+
+    if n.is_one() {
+        result
+    } else {
+        0
+    }
+}
+
+/// Decodes a hex string, ignoring any whitespace in it. Used for test vectors.
+#[cfg(test)]
+pub(crate) fn from_hex(s: &str) -> Vec<u8> {
+    let s: String = s.split_whitespace().collect();
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use rand::prelude::*;
-    use std::time::Instant;
 
-    // #[test]
-    fn _benchmark() {
-        const NUM_ITER: usize = 1;
+    #[test]
+    fn test_i2osp_os2ip() {
+        let x = BigUint::from(0x0102u32);
+
+        assert_eq!(i2osp(&x, 2).unwrap(), vec![0x01, 0x02]);
+        assert_eq!(i2osp(&x, 4).unwrap(), vec![0x00, 0x00, 0x01, 0x02]);
+        assert_eq!(i2osp(&BigUint::ZERO, 3).unwrap(), vec![0x00; 3]);
+        assert!(i2osp(&x, 1).is_err());
 
-        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::from_entropy());
+        assert_eq!(os2ip(&[0x00, 0x00, 0x01, 0x02]), x);
+        assert_eq!(os2ip(&[]), BigUint::ZERO);
 
-        let mut times: [f64; NUM_ITER] = [0.0; NUM_ITER];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        bytes[0] = 0x00;
 
-        let total = Instant::now();
+        assert_eq!(i2osp(&os2ip(&bytes), 64).unwrap(), bytes);
+    }
+
+    const CARMICHAEL_NUMBERS: [u64; 13] = [
+        561,
+        1105,
+        1729,
+        2465,
+        2821,
+        6601,
+        8911,
+        41041,
+        825265,
+        321197185,
+        5394826801,
+        232250619601,
+        9746347772161,
+    ];
+
+    // strong pseudoprimes to base 2
+    const STRONG_PSEUDOPRIMES: [u64; 10] = [
+        2047, 3277, 4033, 4681, 8321, 15841, 29341, 42799, 49141, 52633,
+    ];
+
+    // strong Lucas pseudoprimes, with Selfridge's parameters
+    const STRONG_LUCAS_PSEUDOPRIMES: [u64; 10] = [
+        5459, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519,
+    ];
+
+    // the 1024-bit MODP prime from RFC2409
+    const OAKLEY_GROUP_2_PRIME: &str = "\
+        FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74\
+        020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+        4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+        EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFFFFFF";
+
+    fn known_primes() -> Vec<BigUint> {
+        let one = BigUint::one();
+
+        let mut primes: Vec<BigUint> = PRECOMPUTED_PRIMES
+            .iter()
+            .map(|p| BigUint::from(*p))
+            .collect();
+        primes.push((&one << 61u32) - 1u32);
+        primes.push((&one << 89u32) - 1u32);
+        primes.push((&one << 127u32) - 1u32);
+        primes.push(BigUint::parse_bytes(OAKLEY_GROUP_2_PRIME.as_bytes(), 16).unwrap());
+
+        primes
+    }
+
+    #[test]
+    fn test_primality_known_primes() {
+        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(0));
+
+        for prime in known_primes() {
+            assert!(miller_rabin_is_prime(&mut rng, &prime, 10), "{}", prime);
+            assert!(
+                miller_rabin_with_bases(&prime, &DETERMINISTIC_MILLER_RABIN_BASES),
+                "{}",
+                prime
+            );
+            assert!(strong_lucas_is_prime(&prime), "{}", prime);
+            assert!(baillie_psw_is_prime(&prime), "{}", prime);
+        }
+    }
+
+    #[test]
+    fn test_primality_small_composites() {
+        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(0));
+
+        let primes: Vec<u64> = PRECOMPUTED_PRIMES.to_vec();
+        let largest = *SieveSize::default().primes().last().unwrap();
+        for n in 0..=largest {
+            let expected = primes.contains(&n);
+            let n = BigUint::from(n);
+
+            assert_eq!(miller_rabin_is_prime(&mut rng, &n, 10), expected, "{}", n);
+            assert_eq!(
+                miller_rabin_with_bases(&n, &DETERMINISTIC_MILLER_RABIN_BASES),
+                expected,
+                "{}",
+                n
+            );
+            assert_eq!(baillie_psw_is_prime(&n), expected, "{}", n);
+        }
+
+        // products of two large primes
+        let one = BigUint::one();
+        let m61 = (&one << 61u32) - 1u32;
+        let m89 = (&one << 89u32) - 1u32;
+        let n = &m61 * &m89;
+        assert!(!miller_rabin_is_prime(&mut rng, &n, 10));
+        assert!(!strong_lucas_is_prime(&n));
+        assert!(!baillie_psw_is_prime(&n));
+        assert!(!baillie_psw_is_prime(&(&m89 * &m89)));
+    }
+
+    #[test]
+    fn test_primality_carmichael_numbers() {
+        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(0));
+
+        for n in CARMICHAEL_NUMBERS {
+            let n = BigUint::from(n);
+
+            assert!(!miller_rabin_is_prime(&mut rng, &n, 20), "{}", n);
+            assert!(
+                !miller_rabin_with_bases(&n, &DETERMINISTIC_MILLER_RABIN_BASES),
+                "{}",
+                n
+            );
+            assert!(!baillie_psw_is_prime(&n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_primality_strong_pseudoprimes() {
+        for n in STRONG_PSEUDOPRIMES {
+            let n = BigUint::from(n);
+
+            assert!(miller_rabin_with_bases(&n, &[2]), "{}", n);
+            assert!(!strong_lucas_is_prime(&n), "{}", n);
+            assert!(!baillie_psw_is_prime(&n), "{}", n);
+        }
+
+        for n in STRONG_LUCAS_PSEUDOPRIMES {
+            let n = BigUint::from(n);
+
+            assert!(strong_lucas_is_prime(&n), "{}", n);
+            assert!(!miller_rabin_with_bases(&n, &[2]), "{}", n);
+            assert!(!baillie_psw_is_prime(&n), "{}", n);
+        }
+
+        // strong pseudoprimes to every base up to 23, and to every base up to 37
+        let psi_9 = BigUint::from(3825123056546413051u64);
+        let psi_12 = BigUint::parse_bytes(b"318665857834031151167461", 10).unwrap();
+
+        assert!(miller_rabin_with_bases(
+            &psi_9,
+            &DETERMINISTIC_MILLER_RABIN_BASES[..9]
+        ));
+        assert!(!miller_rabin_with_bases(
+            &psi_9,
+            &DETERMINISTIC_MILLER_RABIN_BASES
+        ));
+        assert!(miller_rabin_with_bases(
+            &psi_12,
+            &DETERMINISTIC_MILLER_RABIN_BASES
+        ));
+        assert!(!baillie_psw_is_prime(&psi_9));
+        assert!(!baillie_psw_is_prime(&psi_12));
+    }
+
+    #[test]
+    fn test_primality_test_impls() {
+        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(0));
+
+        let tests: [Box<dyn PrimalityTest>; 3] = [
+            Box::new(MillerRabin::new(10)),
+            Box::new(BailliePsw),
+            Box::new(FipsMillerRabin::new(1)),
+        ];
+
+        for test in &tests {
+            for prime in known_primes() {
+                assert!(test.is_prime(&mut rng, &prime), "{}", prime);
+            }
+            for n in CARMICHAEL_NUMBERS {
+                assert!(!test.is_prime(&mut rng, &BigUint::from(n)), "{}", n);
+            }
+
+            let prime =
+                generate_prime_with_test(&mut rng, test.as_ref(), 256, SieveSize::default())
+                    .unwrap();
+            assert_eq!(prime.bits(), 256);
+            assert!(baillie_psw_is_prime(&prime));
+        }
+
+        assert_eq!(fips_miller_rabin_rounds(1024), 5);
+        assert_eq!(fips_miller_rabin_rounds(1536), 4);
+    }
+
+    #[test]
+    fn test_sieve_sizes() {
+        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(0));
 
-        for i in times.iter_mut() {
-            let start = Instant::now();
-            // generate_candidate_prime(&mut rng, 1);
-            probable_prime(&mut rng, 10);
-            let milli = start.elapsed().as_secs_f64();
+        // the table holds the first PRECOMPUTED_PRIMES_LEN primes, in order
+        let mut expected = PRECOMPUTED_PRIMES.iter();
+        for n in 0..=PRECOMPUTED_PRIMES[PRECOMPUTED_PRIMES_LEN - 1] {
+            if miller_rabin_with_bases(&BigUint::from(n), &DETERMINISTIC_MILLER_RABIN_BASES) {
+                assert_eq!(expected.next(), Some(&n));
+            }
+        }
+        assert_eq!(expected.next(), None);
+
+        for sieve_size in [
+            SieveSize::Primes256,
+            SieveSize::Primes512,
+            SieveSize::Primes1024,
+            SieveSize::Primes2048,
+            SieveSize::Primes4096,
+        ] {
+            assert_eq!(sieve_size.primes().len(), sieve_size.size());
+
+            let prime = generate_prime_with_test(&mut rng, &BailliePsw, 256, sieve_size).unwrap();
+            assert_eq!(prime.bits(), 256);
+            assert!(sieve_size.primes().iter().all(|p| !(&prime % *p).is_zero()));
+        }
+    }
 
-            *i = milli;
+    #[test]
+    fn test_zeroize_biguint() {
+        for bits in [0, 1, 31, 32, 33, 64, 65, 2048] {
+            let mut x = (BigUint::one() << bits) - 1u32;
+            zeroize_biguint(&mut x);
+            assert!(x.is_zero());
         }
+    }
 
-        println!(
-            "Total elapsed time: {}s\nTotal iterations: {}\nAverage time per iteration: {}s\n",
-            total.elapsed().as_secs_f64(),
-            NUM_ITER,
-            (times.iter().sum::<f64>()) / (NUM_ITER as f64)
+    #[test]
+    fn test_generic_rng() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let prime = generate_candidate_prime(&mut rng, 10, 256).unwrap();
+        assert_eq!(prime.bits(), 256);
+        assert!(miller_rabin_is_prime(&mut rand::thread_rng(), &prime, 10));
+
+        // a boxed rng draws the same primes as the rng it boxes
+        let mut boxed: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(16));
+        assert_eq!(
+            generate_candidate_prime(&mut boxed, 10, 256).unwrap(),
+            prime
         );
+        assert!(miller_rabin_is_prime(boxed.as_mut(), &prime, 10));
+    }
 
-        // println!("{:?}", x);
+    #[test]
+    fn test_prime_bit_lengths() {
+        let mut rng = StdRng::seed_from_u64(19);
+
+        // too short to draw a candidate from, or to ever leave the sieve
+        for bits in [0, 1, 2, 11, RSA_MIN_PRIME_BIT_LENGTH - 1] {
+            assert!(generate_candidate_prime(&mut rng, 10, bits).is_err());
+            assert!(
+                generate_prime_with_test(&mut rng, &BailliePsw, bits, SieveSize::default())
+                    .is_err()
+            );
+            #[cfg(feature = "parallel")]
+            assert!(
+                generate_prime_parallel(&mut rng, &BailliePsw, bits, SieveSize::default(), 2)
+                    .is_err()
+            );
+        }
+
+        let prime = generate_candidate_prime(&mut rng, 10, RSA_MIN_PRIME_BIT_LENGTH).unwrap();
+        assert_eq!(prime.bits(), RSA_MIN_PRIME_BIT_LENGTH);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_generate_prime_parallel() {
+        let mut rng: Box<dyn RsaCsprng> = Box::new(StdRng::seed_from_u64(14));
+
+        for threads in [1, 2, 4] {
+            let prime = generate_prime_parallel(
+                &mut rng,
+                &MillerRabin::new(10),
+                512,
+                SieveSize::default(),
+                threads,
+            )
+            .unwrap();
+            assert_eq!(prime.bits(), 512);
+            assert!(baillie_psw_is_prime(&prime));
+        }
     }
 }